@@ -0,0 +1,77 @@
+//! Dates in the proleptic Gregorian calendar, counted in days since the epoch.
+
+pub const SECONDS_PER_DAY: i64 = 86400;
+/// The years that `struct tm` can hold, like GNU, which rejects any date beyond them. This also
+/// keeps the calendar arithmetic far from overflowing.
+pub const MIN_YEAR: i64 = libc::c_int::MIN as i64 + 1900;
+pub const MAX_YEAR: i64 = libc::c_int::MAX as i64 + 1900;
+
+/// The number of days in a month from 1 to 12.
+pub fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Count the days since the epoch for a date. Returns `None` if the year is out of range.
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> Option<i64> {
+    if !(MIN_YEAR..=MAX_YEAR).contains(&year) {
+        return None;
+    }
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month = i64::from(month);
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    Some(era * 146_097 + day_of_era - 719_468)
+}
+
+/// The inverse of [`days_from_civil`], returning the year, month and day, or `None` if the year
+/// is out of range.
+pub fn civil_from_days(days: i64) -> Option<(i64, u32, u32)> {
+    let days = days.checked_add(719_468)?;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    } as u32;
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+    Some((year, month, day)).filter(|_| (MIN_YEAR..=MAX_YEAR).contains(&year))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn days_round_trip() {
+        assert_eq!(Some(0), days_from_civil(1970, 1, 1));
+        assert_eq!(Some(19_782), days_from_civil(2024, 2, 29));
+        assert_eq!(Some(-719_528), days_from_civil(0, 1, 1));
+        for &days in &[-719_528, -1, 0, 59, 19_782, 2_932_896] {
+            let (year, month, day) = civil_from_days(days).unwrap();
+            assert_eq!(Some(days), days_from_civil(year, month, day));
+        }
+    }
+
+    #[test]
+    fn years_out_of_range() {
+        assert_eq!(None, days_from_civil(MAX_YEAR + 1, 1, 1));
+        assert_eq!(None, days_from_civil(MIN_YEAR - 1, 12, 31));
+        let last = days_from_civil(MAX_YEAR, 12, 31).unwrap();
+        assert_eq!(Some((MAX_YEAR, 12, 31)), civil_from_days(last));
+        assert_eq!(None, civil_from_days(last + 1));
+        assert_eq!(None, civil_from_days(i64::MAX));
+    }
+}
@@ -0,0 +1,960 @@
+//! Free-form date strings like `2024-01-02 03:04`, `yesterday 14:00` or `3 days ago`, as
+//! understood by GNU `touch -d` and `date -d`.
+//!
+//! A date string is made of items in any order, separated by whitespace where needed:
+//!
+//! - calendar dates: `2024-01-02`, `24-01-02`, `20240102`, `1/2/2024`, `1/2`, `2 Jan 2024`,
+//!   `Jan 2, 2024`
+//! - times of day: `14:00`, `03:04:05.123`, `2pm`, `12:30 am`, `1400`
+//! - time zones: `Z`, `UTC`, `GMT`, `EST`, or offsets like `+02:00` and `-0500` right after
+//!   the time of day, so like in GNU, `10:00 -1 hour` is 10:00 at UTC-1 plus an hour
+//! - days of the week: `friday`, `next friday`, `last wed`
+//! - relative items: `3 days ago`, `+1 month`, `-2 weeks`, `next year`, `tomorrow`,
+//!   `yesterday`, `today`, `now`
+//! - seconds since the epoch: `@1700000000`, `@-1.5`, which can't be combined with other items
+//!
+//! Together this covers ISO 8601 (`2024-01-02T03:04:05.123+02:00`) and RFC 2822
+//! (`Tue, 02 Jan 2024 03:04:05 +0200`) timestamps. Anything that isn't given is taken from
+//! the base time, usually the current time. Without a time of day, the string refers to midnight
+//! unless it only has relative items, so `3 days ago` keeps the time and an empty string is the
+//! start of today.
+
+use crate::calendar::{civil_from_days, days_from_civil, days_in_month, SECONDS_PER_DAY};
+use crate::localtime::{self, DateTime};
+use crate::NANOS_PER_SECOND;
+use std::convert::TryFrom;
+use std::str::FromStr;
+
+/// A point in time, as seconds and nanoseconds since the epoch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Timestamp {
+    pub secs: i64,
+    /// Nanoseconds from 0 to 999,999,999, which are added to the seconds even if those are
+    /// negative.
+    pub nanos: u32,
+}
+
+/// Parse a date string relative to the base time. Returns `None` if the string is malformed,
+/// refers to a time that doesn't exist, like February 30, or is out of range for `time_t`.
+pub fn parse(input: &str, base: Timestamp) -> Option<Timestamp> {
+    let tokens = tokenize(input)?;
+    let items = Parser::new(&tokens).parse()?;
+    items
+        .resolve(base)
+        .filter(|time| libc::time_t::try_from(time.secs).is_ok())
+}
+
+/// Check whether the date string gives nothing but a time of day, possibly with a time zone,
+/// like `14:00` or `2pm UTC`. Such a string refers to that time today, even if it has passed.
+pub fn is_time_of_day(input: &str) -> bool {
+    let items = match tokenize(input).and_then(|tokens| Parser::new(&tokens).parse()) {
+        Some(items) => items,
+        None => return false,
+    };
+
+    items.time.is_some()
+        && items.epoch.is_none()
+        && items.date.is_none()
+        && items.weekday.is_none()
+        && !items.relative_seen
+}
+
+/// A single token of the date string.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    /// Decimal digits, kept as text to know their amount.
+    Number(String),
+    /// Letters in lowercase.
+    Word(String),
+    /// Any other character that has a meaning, like `:` or `-`.
+    Symbol(char),
+}
+
+fn tokenize(input: &str) -> Option<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_ascii_digit() || c.is_ascii_alphabetic() {
+            let mut text = String::new();
+            while let Some(&next) = chars.peek() {
+                if next.is_ascii_digit() != c.is_ascii_digit() || !next.is_ascii_alphanumeric() {
+                    break;
+                }
+                text.push(next.to_ascii_lowercase());
+                chars.next();
+            }
+            tokens.push(if c.is_ascii_digit() {
+                Token::Number(text)
+            } else {
+                Token::Word(text)
+            });
+        } else {
+            chars.next();
+            match c {
+                ':' | '-' | '+' | '/' | '.' | ',' | '@' => tokens.push(Token::Symbol(c)),
+                _ if c.is_whitespace() => {}
+                _ => return None,
+            }
+        }
+    }
+
+    Some(tokens)
+}
+
+/// A calendar date, possibly without a year.
+#[derive(Clone, Copy, Debug)]
+struct Date {
+    year: Option<i64>,
+    month: u32,
+    day: u32,
+}
+
+/// A time of day.
+#[derive(Clone, Copy, Debug, Default)]
+struct Time {
+    hour: u32,
+    minute: u32,
+    second: u32,
+    nanos: u32,
+}
+
+/// Amounts of each unit to add, which may be negative.
+#[derive(Clone, Copy, Debug, Default)]
+struct Relative {
+    months: i64,
+    days: i64,
+    seconds: i64,
+}
+
+/// All items of a date string.
+#[derive(Debug, Default)]
+struct Items {
+    epoch: Option<Timestamp>,
+    date: Option<Date>,
+    time: Option<Time>,
+    /// Offset from UTC in seconds.
+    zone: Option<i64>,
+    /// Day of the week from 0 for sunday, along with the ordinal like -1 for `last`.
+    weekday: Option<(u32, i64)>,
+    relative: Relative,
+    /// Whether any relative item was given, even one like `now` that doesn't change anything.
+    relative_seen: bool,
+    /// Whether there is anything besides the epoch.
+    others: bool,
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+    items: Items,
+}
+
+impl<'a> Parser<'a> {
+    fn new(tokens: &'a [Token]) -> Self {
+        Self {
+            tokens,
+            pos: 0,
+            items: Items::default(),
+        }
+    }
+
+    fn parse(mut self) -> Option<Items> {
+        while self.pos < self.tokens.len() {
+            self.item()?;
+        }
+
+        if self.items.epoch.is_some() && self.items.others {
+            return None;
+        }
+        Some(self.items)
+    }
+
+    fn peek(&self, offset: usize) -> Option<&'a Token> {
+        self.tokens.get(self.pos + offset)
+    }
+
+    fn next(&mut self) -> Option<&'a Token> {
+        let token = self.tokens.get(self.pos)?;
+        self.pos += 1;
+        Some(token)
+    }
+
+    fn is_symbol(&self, offset: usize, symbol: char) -> bool {
+        self.peek(offset) == Some(&Token::Symbol(symbol))
+    }
+
+    fn number(&mut self) -> Option<&'a str> {
+        match self.next()? {
+            Token::Number(digits) => Some(digits),
+            _ => None,
+        }
+    }
+
+    fn value<T: FromStr>(&mut self) -> Option<T> {
+        self.number()?.parse().ok()
+    }
+
+    fn symbol(&mut self, symbol: char) -> Option<()> {
+        match self.next()? {
+            Token::Symbol(c) if *c == symbol => Some(()),
+            _ => None,
+        }
+    }
+
+    /// Parse the next item, whatever it is.
+    fn item(&mut self) -> Option<()> {
+        match self.peek(0)? {
+            Token::Symbol('@') => self.epoch(),
+            Token::Symbol(sign @ '+') | Token::Symbol(sign @ '-') => {
+                self.pos += 1;
+                let amount: i64 = self.value()?;
+                self.relative(if *sign == '-' { -amount } else { amount })
+            }
+            Token::Symbol(',') => {
+                self.pos += 1;
+                Some(())
+            }
+            Token::Symbol(_) => None,
+            Token::Number(digits) => self.numeric(digits),
+            Token::Word(word) => self.word(word),
+        }
+    }
+
+    /// Parse `@SECONDS[.FRACTION]`, where the seconds may be negative.
+    fn epoch(&mut self) -> Option<()> {
+        self.symbol('@')?;
+        let negative = self.is_symbol(0, '-');
+        if negative || self.is_symbol(0, '+') {
+            self.pos += 1;
+        }
+
+        let secs: i64 = self.value()?;
+        let nanos = if self.is_symbol(0, '.') || self.is_symbol(0, ',') {
+            self.pos += 1;
+            fraction(self.number()?)
+        } else {
+            0
+        };
+
+        // Nanoseconds always count forward, so a negative fraction borrows a second.
+        let (secs, nanos) = match (negative, nanos) {
+            (false, _) => (secs, nanos),
+            (true, 0) => (-secs, 0),
+            (true, _) => (-secs - 1, NANOS_PER_SECOND - nanos),
+        };
+
+        set_once(&mut self.items.epoch, Timestamp { secs, nanos })
+    }
+
+    /// Parse an item that starts with a number.
+    fn numeric(&mut self, digits: &str) -> Option<()> {
+        self.items.others = true;
+
+        match self.peek(1) {
+            Some(Token::Symbol('-')) if digits.len() >= 3 || self.is_symbol(3, '-') => {
+                self.iso_date()
+            }
+            Some(Token::Symbol('/')) => self.us_date(),
+            Some(Token::Symbol(':')) => self.time(),
+            Some(Token::Word(word)) if month(word).is_some() => self.day_month(),
+            Some(Token::Word(word)) if unit(word).is_some() => {
+                let amount: i64 = self.value()?;
+                self.relative(amount)
+            }
+            Some(Token::Word(word)) if meridiem(word).is_some() => self.time(),
+            _ if digits.len() > 4 => {
+                // YYYYMMDD, with as many digits for the year as needed
+                let value: i64 = digits.parse().ok()?;
+                self.pos += 1;
+                self.set_date(
+                    Some(value / 10000),
+                    (value / 100 % 100) as u32,
+                    (value % 100) as u32,
+                )
+            }
+            _ => self.time(),
+        }
+    }
+
+    /// Parse `YYYY-MM-DD` or `YY-MM-DD`, optionally followed by `T` and the time of day.
+    fn iso_date(&mut self) -> Option<()> {
+        let year = self.year()?;
+        self.symbol('-')?;
+        let month = self.value()?;
+        self.symbol('-')?;
+        let day = self.value()?;
+        self.set_date(Some(year), month, day)?;
+
+        if self.peek(0) == Some(&Token::Word("t".to_owned())) {
+            self.pos += 1;
+            self.time()?;
+        }
+        Some(())
+    }
+
+    /// Parse `MM/DD` or `MM/DD/YYYY`.
+    fn us_date(&mut self) -> Option<()> {
+        let month = self.value()?;
+        self.symbol('/')?;
+        let day = self.value()?;
+        let year = if self.is_symbol(0, '/') {
+            self.pos += 1;
+            Some(self.year()?)
+        } else {
+            None
+        };
+        self.set_date(year, month, day)
+    }
+
+    /// Parse `DD Month [YYYY]`.
+    fn day_month(&mut self) -> Option<()> {
+        let day = self.value()?;
+        let month = self.month()?;
+        let year = self.optional_year()?;
+        self.set_date(year, month, day)
+    }
+
+    /// Parse `Month DD [YYYY]`, with an optional comma before the year.
+    fn month_day(&mut self) -> Option<()> {
+        let month = self.month()?;
+        let day = self.value()?;
+        if self.is_symbol(0, ',') && matches!(self.peek(1), Some(Token::Number(_))) {
+            self.pos += 1;
+        }
+        let year = self.optional_year()?;
+        self.set_date(year, month, day)
+    }
+
+    /// Parse the year after a month name, unless the next number is a time like `10:00`.
+    fn optional_year(&mut self) -> Option<Option<i64>> {
+        match self.peek(0) {
+            Some(Token::Number(_)) if !self.is_symbol(1, ':') => self.year().map(Some),
+            _ => Some(None),
+        }
+    }
+
+    fn month(&mut self) -> Option<u32> {
+        match self.next()? {
+            Token::Word(word) => month(word),
+            _ => None,
+        }
+    }
+
+    /// Parse a year, where two digits stand for 1969 to 2068.
+    fn year(&mut self) -> Option<i64> {
+        let digits = self.number()?;
+        let year: i64 = digits.parse().ok()?;
+        Some(match (digits.len(), year) {
+            (2, 69..=99) => 1900 + year,
+            (2, _) => 2000 + year,
+            _ => year,
+        })
+    }
+
+    fn set_date(&mut self, year: Option<i64>, month: u32, day: u32) -> Option<()> {
+        if !(1..=12).contains(&month) || day == 0 || day > 31 {
+            return None;
+        }
+        set_once(&mut self.items.date, Date { year, month, day })
+    }
+
+    /// Parse `HH[:MM[:SS[.FRACTION]]]` or `HHMM`, followed by either `am` or `pm`, or a time
+    /// zone offset.
+    fn time(&mut self) -> Option<()> {
+        let digits = self.number()?;
+        let (mut hour, mut minute) = match digits.len() {
+            1 | 2 => (digits.parse().ok()?, 0),
+            3 | 4 => {
+                let value: u32 = digits.parse().ok()?;
+                (value / 100, value % 100)
+            }
+            _ => return None,
+        };
+        let mut second = 0;
+        let mut nanos = 0;
+        let mut minutes = false;
+        let mut meridiem_seen = false;
+
+        if digits.len() <= 2 && self.is_symbol(0, ':') {
+            self.pos += 1;
+            minutes = true;
+            minute = self.value()?;
+            if self.is_symbol(0, ':') {
+                self.pos += 1;
+                second = self.value()?;
+                if self.is_symbol(0, '.') || self.is_symbol(0, ',') {
+                    self.pos += 1;
+                    nanos = fraction(self.number()?);
+                }
+            }
+        }
+
+        if let Some(Token::Word(word)) = self.peek(0) {
+            if let Some(pm) = meridiem(word) {
+                self.pos += 1;
+                meridiem_seen = true;
+                if !(1..=12).contains(&hour) {
+                    return None;
+                }
+                hour = hour % 12 + if pm { 12 } else { 0 };
+            }
+        }
+
+        if hour > 23 || minute > 59 || second > 59 {
+            return None;
+        }
+
+        set_once(
+            &mut self.items.time,
+            Time {
+                hour,
+                minute,
+                second,
+                nanos,
+            },
+        )?;
+        if meridiem_seen {
+            // Like in GNU, there is no offset after `am` or `pm`.
+            return Some(());
+        }
+        self.numeric_zone(minutes)
+    }
+
+    /// Parse a time zone offset like `+02:00`, `-0500` or `+2` right after the time of day. Like
+    /// in GNU, a number followed by a unit is a relative item instead, unless the time has
+    /// minutes after a colon: `10 -3 hours` is three hours earlier, but `10:00 -3 hours` is 10:00
+    /// at UTC-3 plus one hour.
+    fn numeric_zone(&mut self, minutes: bool) -> Option<()> {
+        let sign = match self.peek(0) {
+            Some(Token::Symbol('+')) => 1,
+            Some(Token::Symbol('-')) => -1,
+            _ => return Some(()),
+        };
+        if let Some(Token::Word(word)) = self.peek(2) {
+            if !minutes && unit(word).is_some() {
+                return Some(());
+            }
+        }
+
+        self.pos += 1;
+        let digits = self.number()?;
+        let (hours, minutes): (i64, i64) = match digits.len() {
+            1 | 2 if self.is_symbol(0, ':') => {
+                self.pos += 1;
+                let minutes = self.number()?;
+                if minutes.len() != 2 {
+                    return None;
+                }
+                (digits.parse().ok()?, minutes.parse().ok()?)
+            }
+            1 | 2 => (digits.parse().ok()?, 0),
+            4 => (digits[..2].parse().ok()?, digits[2..].parse().ok()?),
+            _ => return None,
+        };
+
+        if hours > 24 || minutes > 59 {
+            return None;
+        }
+        self.set_zone(sign * (hours * 3600 + minutes * 60))
+    }
+
+    fn set_zone(&mut self, offset: i64) -> Option<()> {
+        set_once(&mut self.items.zone, offset)
+    }
+
+    /// Parse an item that starts with a word.
+    fn word(&mut self, word: &str) -> Option<()> {
+        self.items.others = true;
+
+        if month(word).is_some() {
+            return self.month_day();
+        }
+
+        self.pos += 1;
+
+        if let Some(offset) = zone(word) {
+            return self.set_zone(offset);
+        }
+        if let Some(weekday) = weekday(word) {
+            return self.weekday(weekday, 0);
+        }
+        if unit(word).is_some() {
+            self.pos -= 1;
+            return self.relative(1);
+        }
+
+        match word {
+            "tomorrow" => self.items.relative.days += 1,
+            "yesterday" => self.items.relative.days -= 1,
+            "today" | "now" => {}
+            _ => {
+                let ordinal = ordinal(word)?;
+                return match self.peek(0)? {
+                    Token::Word(next) => match weekday(next) {
+                        Some(weekday) => {
+                            self.pos += 1;
+                            self.weekday(weekday, ordinal)
+                        }
+                        None => self.relative(ordinal),
+                    },
+                    _ => None,
+                };
+            }
+        }
+        self.items.relative_seen = true;
+        Some(())
+    }
+
+    fn weekday(&mut self, weekday: u32, ordinal: i64) -> Option<()> {
+        if self.is_symbol(0, ',') {
+            self.pos += 1;
+        }
+        set_once(&mut self.items.weekday, (weekday, ordinal))
+    }
+
+    /// Parse the unit of a relative item with the given amount, followed by an optional `ago`.
+    fn relative(&mut self, amount: i64) -> Option<()> {
+        self.items.others = true;
+        self.items.relative_seen = true;
+
+        let unit = match self.next()? {
+            Token::Word(word) => unit(word)?,
+            _ => return None,
+        };
+        let amount = if self.peek(0) == Some(&Token::Word("ago".to_owned())) {
+            self.pos += 1;
+            -amount
+        } else {
+            amount
+        };
+
+        let relative = &mut self.items.relative;
+        let target = match unit {
+            Unit::Months(factor) => (&mut relative.months, factor),
+            Unit::Days(factor) => (&mut relative.days, factor),
+            Unit::Seconds(factor) => (&mut relative.seconds, factor),
+        };
+        *target.0 = amount.checked_mul(target.1)?.checked_add(*target.0)?;
+        Some(())
+    }
+}
+
+impl Items {
+    /// Calculate the point in time the items refer to.
+    fn resolve(&self, base: Timestamp) -> Option<Timestamp> {
+        if let Some(epoch) = self.epoch {
+            return Some(epoch);
+        }
+
+        // Like in GNU date, anything that isn't given comes from the local time, even if the
+        // items name another time zone.
+        let start = localtime::to_local(base.secs)?;
+
+        let mut year = start.year;
+        let mut month = start.month;
+        let mut day = start.day;
+        if let Some(date) = self.date {
+            year = date.year.unwrap_or(year);
+            month = date.month;
+            day = date.day;
+            if day > days_in_month(year, month) {
+                return None;
+            }
+        }
+
+        let relative_only = self.relative_seen
+            && self.date.is_none()
+            && self.time.is_none()
+            && self.weekday.is_none();
+        let time = match self.time {
+            Some(time) => time,
+            None if relative_only => Time {
+                hour: start.hour,
+                minute: start.minute,
+                second: start.second,
+                nanos: base.nanos,
+            },
+            None => Time::default(),
+        };
+
+        let mut days = days_from_civil(year, month, 1)? + i64::from(day) - 1;
+
+        // A day of the week only counts if there is no calendar date.
+        if let (Some((weekday, ordinal)), None) = (self.weekday, self.date) {
+            let current = (days + 4).rem_euclid(7);
+            let ahead = (i64::from(weekday) - current).rem_euclid(7);
+            days += ahead + 7 * (ordinal - (ordinal > 0 && ahead != 0) as i64);
+        }
+
+        // Months and days move the calendar date. Days that don't exist in the resulting month
+        // carry over into the next one.
+        let start_days = days;
+        let (year, month, day) = civil_from_days(days)?;
+        let months = (year * 12 + i64::from(month) - 1).checked_add(self.relative.months)?;
+        let month_start =
+            days_from_civil(months.div_euclid(12), months.rem_euclid(12) as u32 + 1, 1)?;
+        days = (month_start + i64::from(day) - 1).checked_add(self.relative.days)?;
+
+        let (year, month, day) = civil_from_days(days)?;
+        let datetime = DateTime {
+            year,
+            month,
+            day,
+            hour: time.hour,
+            minute: time.minute,
+            second: time.second,
+        };
+
+        let secs = match self.zone {
+            Some(offset) => days
+                .checked_mul(SECONDS_PER_DAY)?
+                .checked_add(i64::from(time.hour * 3600 + time.minute * 60 + time.second))?
+                .checked_sub(offset)?,
+            // GNU keeps the offset from UTC of the base time if there are only relative items,
+            // so a day is always 24 hours then. Otherwise the time of day stays the same even
+            // across changes of daylight saving time.
+            None if relative_only => base
+                .secs
+                .checked_add((days - start_days).checked_mul(SECONDS_PER_DAY)?)?,
+            None => localtime::from_local(datetime)?,
+        };
+
+        Some(Timestamp {
+            secs: secs.checked_add(self.relative.seconds)?,
+            nanos: time.nanos,
+        })
+    }
+}
+
+/// Set an item that may only be given once.
+fn set_once<T>(item: &mut Option<T>, value: T) -> Option<()> {
+    match item {
+        Some(_) => None,
+        None => {
+            *item = Some(value);
+            Some(())
+        }
+    }
+}
+
+/// Convert the digits after a decimal point into nanoseconds, ignoring any beyond the ninth.
+fn fraction(digits: &str) -> u32 {
+    digits
+        .bytes()
+        .chain(std::iter::repeat(b'0'))
+        .take(9)
+        .fold(0, |nanos, d| nanos * 10 + u32::from(d - b'0'))
+}
+
+/// The unit of a relative item, as a multiple of the unit it's counted in.
+#[derive(Clone, Copy)]
+enum Unit {
+    Months(i64),
+    Days(i64),
+    Seconds(i64),
+}
+
+fn unit(word: &str) -> Option<Unit> {
+    let word = word.strip_suffix('s').unwrap_or(word);
+    Some(match word {
+        "year" => Unit::Months(12),
+        "month" => Unit::Months(1),
+        "fortnight" => Unit::Days(14),
+        "week" => Unit::Days(7),
+        "day" => Unit::Days(1),
+        "hour" => Unit::Seconds(3600),
+        "minute" | "min" => Unit::Seconds(60),
+        "second" | "sec" => Unit::Seconds(1),
+        _ => return None,
+    })
+}
+
+/// Words that stand for a number in front of a unit or day of the week. `second` is missing, as
+/// it's a unit.
+fn ordinal(word: &str) -> Option<i64> {
+    Some(match word {
+        "last" => -1,
+        "this" => 0,
+        "next" | "first" => 1,
+        "third" => 3,
+        "fourth" => 4,
+        "fifth" => 5,
+        "sixth" => 6,
+        "seventh" => 7,
+        "eighth" => 8,
+        "ninth" => 9,
+        "tenth" => 10,
+        "eleventh" => 11,
+        "twelfth" => 12,
+        _ => return None,
+    })
+}
+
+const MONTHS: [&str; 12] = [
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+];
+
+const WEEKDAYS: [&str; 7] = [
+    "sunday",
+    "monday",
+    "tuesday",
+    "wednesday",
+    "thursday",
+    "friday",
+    "saturday",
+];
+
+/// Find a name that is either written out or abbreviated to at least three letters, returning
+/// its index.
+fn find_name(names: &[&str], word: &str) -> Option<usize> {
+    if word.len() < 3 {
+        return None;
+    }
+    names.iter().position(|name| name.starts_with(word))
+}
+
+/// The month from 1 to 12 for a name like `jan` or `january`.
+fn month(word: &str) -> Option<u32> {
+    find_name(&MONTHS, word).map(|index| index as u32 + 1)
+}
+
+/// The day of the week from 0 for sunday for a name like `fri` or `friday`.
+fn weekday(word: &str) -> Option<u32> {
+    find_name(&WEEKDAYS, word).map(|index| index as u32)
+}
+
+/// Whether `am` or `pm` follows a time, returning `true` for the latter.
+fn meridiem(word: &str) -> Option<bool> {
+    match word {
+        "am" => Some(false),
+        "pm" => Some(true),
+        _ => None,
+    }
+}
+
+/// The offset from UTC in seconds for time zone names, including the US ones from RFC 2822.
+fn zone(word: &str) -> Option<i64> {
+    let hours = match word {
+        "z" | "ut" | "utc" | "gmt" => 0,
+        "edt" => -4,
+        "est" | "cdt" => -5,
+        "cst" | "mdt" => -6,
+        "mst" | "pdt" => -7,
+        "pst" => -8,
+        _ => return None,
+    };
+    Some(hours * 3600)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::sync::Once;
+
+    extern "C" {
+        fn tzset();
+    }
+
+    /// Tuesday, 2024-03-05 10:20:30.5 UTC
+    const BASE: Timestamp = Timestamp {
+        secs: 1_709_634_030,
+        nanos: 500_000_000,
+    };
+
+    /// Make UTC the local time zone. Every test calls this before parsing, as the tests run in
+    /// parallel and `TZ` must not change while another thread reads it.
+    fn set_utc() {
+        static SET_TZ: Once = Once::new();
+        SET_TZ.call_once(|| {
+            env::set_var("TZ", "UTC0");
+            // SAFETY: tzset has no preconditions.
+            unsafe { tzset() };
+        });
+    }
+
+    /// Parse the input with UTC as the local time zone.
+    fn utc(input: &str) -> Option<(i64, u32)> {
+        set_utc();
+        parse(input, BASE).map(|t| (t.secs, t.nanos))
+    }
+
+    fn at(year: i64, month: u32, day: u32, hour: u32, minute: u32, second: u32) -> i64 {
+        days_from_civil(year, month, day).unwrap() * SECONDS_PER_DAY
+            + i64::from(hour * 3600 + minute * 60 + second)
+    }
+
+    #[test]
+    fn absolute_dates_and_times() {
+        assert_eq!(Some((at(2024, 1, 2, 0, 0, 0), 0)), utc("2024-01-02"));
+        assert_eq!(Some((at(2024, 1, 2, 0, 0, 0), 0)), utc("20240102"));
+        assert_eq!(Some((at(2024, 1, 2, 0, 0, 0), 0)), utc("24-01-02"));
+        assert_eq!(Some((at(1969, 1, 2, 0, 0, 0), 0)), utc("69-01-02"));
+        assert_eq!(Some((at(2024, 1, 2, 0, 0, 0), 0)), utc("1/2/2024"));
+        assert_eq!(Some((at(2024, 12, 25, 0, 0, 0), 0)), utc("12/25"));
+        assert_eq!(Some((at(2024, 1, 5, 0, 0, 0), 0)), utc("5 jan 2024"));
+        assert_eq!(Some((at(2023, 1, 5, 0, 0, 0), 0)), utc("Jan 5, 2023"));
+        assert_eq!(Some((at(2024, 1, 5, 0, 0, 0), 0)), utc("january 5"));
+        assert_eq!(Some((at(2024, 3, 5, 14, 0, 0), 0)), utc("14:00"));
+        assert_eq!(Some((at(2024, 3, 5, 14, 0, 0), 0)), utc("2pm"));
+        assert_eq!(Some((at(2024, 3, 5, 14, 0, 0), 0)), utc("1400"));
+        assert_eq!(Some((at(2024, 3, 5, 0, 30, 0), 0)), utc("12:30 am"));
+        assert_eq!(
+            Some((at(2024, 1, 2, 3, 4, 5), 123_000_000)),
+            utc("2024-01-02 03:04:05.123")
+        );
+    }
+
+    #[test]
+    fn iso_and_rfc_2822_with_offsets() {
+        set_utc();
+        let expected = Some(Timestamp {
+            secs: at(2024, 1, 2, 1, 4, 5),
+            nanos: 123_000_000,
+        });
+        assert_eq!(expected, parse("2024-01-02T03:04:05.123+02:00", BASE));
+        assert_eq!(
+            Some(at(2024, 1, 2, 1, 4, 5)),
+            parse("Tue, 02 Jan 2024 03:04:05 +0200", BASE).map(|t| t.secs)
+        );
+        assert_eq!(
+            Some(at(2024, 1, 2, 15, 0, 0)),
+            parse("2024-01-02 10:00 EST", BASE).map(|t| t.secs)
+        );
+        assert_eq!(
+            Some(at(2024, 1, 2, 10, 0, 0)),
+            parse("2024-01-02T10:00Z", BASE).map(|t| t.secs)
+        );
+        // A signed number after `HH:MM` is an offset even when a unit follows, unlike after `HH`.
+        assert_eq!(
+            Some(at(2024, 1, 2, 12, 0, 0)),
+            parse("2024-01-02 10:00 -1 hour", BASE).map(|t| t.secs)
+        );
+        assert_eq!(
+            Some(at(2024, 1, 2, 12, 30, 0)),
+            parse("2024-01-02 10:00 -0130 hours", BASE).map(|t| t.secs)
+        );
+        assert_eq!(
+            Some(at(2024, 1, 2, 7, 0, 0)),
+            parse("2024-01-02 10 -3 hours", BASE).map(|t| t.secs)
+        );
+    }
+
+    #[test]
+    fn relative_items() {
+        assert_eq!(
+            Some((at(2024, 3, 2, 10, 20, 30), 500_000_000)),
+            utc("3 days ago")
+        );
+        assert_eq!(
+            Some((at(2024, 3, 6, 10, 20, 30), 500_000_000)),
+            utc("tomorrow")
+        );
+        assert_eq!(Some((at(2024, 3, 4, 14, 0, 0), 0)), utc("yesterday 14:00"));
+        assert_eq!(
+            Some((at(2024, 2, 20, 10, 20, 30), 500_000_000)),
+            utc("-2 weeks")
+        );
+        assert_eq!(
+            Some((at(2024, 3, 2, 0, 0, 0), 0)),
+            utc("2024-01-31 +1 month")
+        );
+        assert_eq!(
+            Some((at(2024, 3, 4, 12, 20, 30), 500_000_000)),
+            utc("1 day ago 2 hours")
+        );
+        assert_eq!(
+            Some((at(2024, 1, 2, 9, 0, 0), 0)),
+            utc("2024-01-02 10:00 UTC -1 hour")
+        );
+        assert_eq!(
+            Some((at(2025, 3, 5, 10, 20, 30), 500_000_000)),
+            utc("next year")
+        );
+        assert_eq!(Some((BASE.secs, BASE.nanos)), utc("now"));
+        // Without any relative item, it's the start of the day.
+        assert_eq!(Some((at(2024, 3, 5, 0, 0, 0), 0)), utc(""));
+    }
+
+    #[test]
+    fn days_of_the_week() {
+        assert_eq!(Some((at(2024, 3, 8, 0, 0, 0), 0)), utc("friday"));
+        assert_eq!(Some((at(2024, 3, 5, 0, 0, 0), 0)), utc("tue"));
+        assert_eq!(Some((at(2024, 3, 12, 0, 0, 0), 0)), utc("next tuesday"));
+        assert_eq!(Some((at(2024, 2, 28, 0, 0, 0), 0)), utc("last wednesday"));
+        // A calendar date takes precedence.
+        assert_eq!(Some((at(2024, 3, 6, 0, 0, 0), 0)), utc("monday 2024-03-06"));
+    }
+
+    #[test]
+    fn only_a_time_of_day() {
+        for input in &["14:00", "2pm", "03:04:05.5", "12:00 UTC", "10:00 +0200"] {
+            assert!(is_time_of_day(input), "{}", input);
+        }
+        for input in &[
+            "",
+            "today",
+            "14:00 tomorrow",
+            "2024-01-02 14:00",
+            "friday 2pm",
+            "@0",
+            "x",
+        ] {
+            assert!(!is_time_of_day(input), "{}", input);
+        }
+    }
+
+    #[test]
+    fn epoch_seconds() {
+        set_utc();
+        let epoch = |secs, nanos| Some(Timestamp { secs, nanos });
+        assert_eq!(epoch(1_700_000_000, 0), parse("@1700000000", BASE));
+        assert_eq!(epoch(-2, 500_000_000), parse("@-1.5", BASE));
+        assert_eq!(epoch(0, 250_000_000), parse(" @0.25 ", BASE));
+        assert_eq!(epoch(1, 0), parse("@+1", BASE));
+        assert_eq!(None, parse("@1 tomorrow", BASE));
+    }
+
+    #[test]
+    fn reject_invalid_dates() {
+        for input in &[
+            "2024-13-01",
+            "2023-02-29",
+            "jan 32",
+            "24:00",
+            "13pm",
+            "10:60",
+            "tomorrow tomorrowx",
+            "3 days since",
+            "ago",
+            "10:00 +25",
+            "10:00 -25 minutes",
+            "10am +0100",
+            "14:00 15:00",
+            "2024-01-02 2024-01-03",
+            "yesterday!",
+            "@",
+            "700000000000000000 months",
+            "99999999999990101 UTC",
+            "100000000000 years",
+            "1000000000000 days",
+            "2147485548-01-01",
+        ] {
+            assert_eq!(None, utc(input), "{}", input);
+        }
+        // The last year that GNU accepts, as `struct tm` can still hold it.
+        assert!(utc("2147485547-12-31").is_some());
+    }
+}
@@ -0,0 +1,356 @@
+//! parser for time intervals like `1.5`, `2m`, `0x1p-4h` or `inf`, as taken by `sleep` and any
+//! other utility that accepts durations on the command line
+
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::NANOS_PER_SECOND;
+
+/// digits are only added to the mantissa while it's below this limit, which is far more
+/// precision than needed and keeps the conversion to nanoseconds from overflowing
+const MANTISSA_LIMIT: u128 = 1 << 60;
+
+/// longest time to wait at once, which even a 32-bit `time_t` can represent. longer durations,
+/// even infinite ones, are waited for in several steps of at most this length
+pub const MAX_WAIT: Duration = Duration::from_secs(i32::MAX as u64);
+
+/// unit of a time interval, given as suffix after the number
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Unit {
+    Seconds,
+    Minutes,
+    Hours,
+    Days,
+}
+
+impl Unit {
+    /// all units, from the smallest to the largest
+    pub const ALL: [Unit; 4] = [Unit::Seconds, Unit::Minutes, Unit::Hours, Unit::Days];
+
+    /// the length of the unit in seconds
+    pub fn seconds(self) -> u128 {
+        match self {
+            Unit::Seconds => 1,
+            Unit::Minutes => 60,
+            Unit::Hours => 3600,
+            Unit::Days => 86400,
+        }
+    }
+
+    /// the suffix that selects the unit
+    pub fn suffix(self) -> &'static str {
+        match self {
+            Unit::Seconds => "s",
+            Unit::Minutes => "m",
+            Unit::Hours => "h",
+            Unit::Days => "d",
+        }
+    }
+
+    fn from_suffix(suffix: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|unit| unit.suffix() == suffix)
+    }
+}
+
+/// a non-negative number as written by the user, with the value `mantissa * radix^exponent`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Number {
+    mantissa: u128,
+    radix: u128,
+    exponent: i32,
+}
+
+impl Number {
+    /// convert the number to nanoseconds, after multiplying it with a unit given in seconds.
+    /// fractions of a nanosecond are rounded up, so that a non-zero interval never results in
+    /// not sleeping at all. returns `None` if the result is too large
+    fn to_nanos(self, unit: u128) -> Option<u128> {
+        let nanos = self
+            .mantissa
+            .checked_mul(unit)?
+            .checked_mul(NANOS_PER_SECOND.into())?;
+        if nanos == 0 {
+            return Some(0);
+        }
+
+        let scale = self.radix.checked_pow(self.exponent.unsigned_abs());
+
+        if self.exponent >= 0 {
+            nanos.checked_mul(scale?)
+        } else {
+            // a divisor that doesn't even fit into an u128 leaves less than one nanosecond
+            Some(scale.map_or((nanos > 0) as u128, |scale| {
+                nanos / scale + (nanos % scale > 0) as u128
+            }))
+        }
+    }
+}
+
+/// one time interval as given on the command line, made of a number and an optional unit
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DurationSpec {
+    number: Number,
+    unit: Option<Unit>,
+}
+
+impl DurationSpec {
+    /// parse one token like `1.5`, `2m` or `0x1p-4h`. the number is read like `strtod` does,
+    /// that is decimal with an optional exponent, hexadecimal with an optional binary exponent,
+    /// or `inf`/`infinity`, and may be followed by one of the suffixes `s`, `m`, `h` and `d`
+    pub fn parse(token: &str) -> Result<Self, ParseError> {
+        let (number, suffix) = parse_number(token)
+            .ok_or_else(|| ParseError::new(token, ErrorKind::InvalidNumber, 0..token.len()))?;
+
+        let unit = match suffix {
+            "" => None,
+            _ => Some(Unit::from_suffix(suffix).ok_or_else(|| {
+                let start = token.len() - suffix.len();
+                ParseError::new(token, ErrorKind::InvalidUnit, start..token.len())
+            })?),
+        };
+
+        Ok(Self { number, unit })
+    }
+
+    /// the unit given as suffix, or `None` if there was none
+    pub fn explicit_unit(&self) -> Option<Unit> {
+        self.unit
+    }
+
+    /// the unit the number is counted in, which defaults to seconds
+    pub fn unit(&self) -> Unit {
+        self.unit.unwrap_or(Unit::Seconds)
+    }
+
+    /// convert to a duration with nanosecond precision, rounding fractions of a nanosecond up.
+    /// intervals that are too large saturate to `Duration::MAX`, as they are effectively
+    /// infinite anyway
+    pub fn to_duration(&self) -> Duration {
+        let nanos = self
+            .number
+            .to_nanos(self.unit().seconds())
+            .unwrap_or(u128::MAX);
+        let per_second = u128::from(NANOS_PER_SECOND);
+        match u64::try_from(nanos / per_second) {
+            Ok(secs) => Duration::new(secs, (nanos % per_second) as u32),
+            Err(_) => Duration::MAX,
+        }
+    }
+}
+
+impl FromStr for DurationSpec {
+    type Err = ParseError;
+
+    fn from_str(token: &str) -> Result<Self, Self::Err> {
+        Self::parse(token)
+    }
+}
+
+/// add up the intervals like multiple operands of `sleep`, saturating on overflow
+pub fn total<'a>(specs: impl IntoIterator<Item = &'a DurationSpec>) -> Duration {
+    specs.into_iter().fold(Duration::ZERO, |sum, spec| {
+        sum.saturating_add(spec.to_duration())
+    })
+}
+
+/// what is wrong with a token
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// the token doesn't start with a number
+    InvalidNumber,
+    /// the number is followed by something other than a known unit
+    InvalidUnit,
+}
+
+/// error for a token that isn't a valid time interval, pointing to the offending part
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    token: String,
+    kind: ErrorKind,
+    span: Range<usize>,
+}
+
+impl ParseError {
+    fn new(token: &str, kind: ErrorKind, span: Range<usize>) -> Self {
+        Self {
+            token: token.to_owned(),
+            kind,
+            span,
+        }
+    }
+
+    /// the whole token that failed to parse
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// byte range of the offending part within the token
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid time interval '{}'", self.token)
+    }
+}
+
+impl Error for ParseError {}
+
+/// parse a number from the start of the input like `strtod` does. returns the number and the
+/// remaining input
+fn parse_number(input: &str) -> Option<(Number, &str)> {
+    let input = input.strip_prefix('+').unwrap_or(input);
+
+    for name in &["infinity", "inf"] {
+        if input
+            .get(..name.len())
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case(name))
+        {
+            // infinity is just a number that is too large to be represented, like any other
+            let number = Number {
+                mantissa: 1,
+                radix: 10,
+                exponent: i32::MAX,
+            };
+            return Some((number, &input[name.len()..]));
+        }
+    }
+
+    let hex = input
+        .get(..2)
+        .filter(|prefix| prefix.eq_ignore_ascii_case("0x"))
+        .map(|_| &input[2..])
+        .filter(|rest| {
+            rest.trim_start_matches('.')
+                .starts_with(|c: char| c.is_ascii_hexdigit())
+        });
+
+    let (radix, digit_radix, digit_exponent, exponent_chars, input) = match hex {
+        Some(rest) => (2, 16, 4, ['p', 'P'], rest),
+        None => (10, 10, 1, ['e', 'E'], input),
+    };
+
+    let mut number = Number {
+        mantissa: 0,
+        radix,
+        exponent: 0,
+    };
+    let mut seen_digit = false;
+    let mut fraction = false;
+    let mut rest = input;
+
+    while let Some(c) = rest.chars().next() {
+        match c.to_digit(digit_radix) {
+            Some(digit) => {
+                seen_digit = true;
+                if number.mantissa < MANTISSA_LIMIT {
+                    number.mantissa = number.mantissa * u128::from(digit_radix) + u128::from(digit);
+                    if fraction {
+                        number.exponent -= digit_exponent;
+                    }
+                } else if !fraction {
+                    // ignore digits that exceed the precision, only their magnitude still counts
+                    number.exponent = number.exponent.saturating_add(digit_exponent);
+                }
+            }
+            None if c == '.' && !fraction => fraction = true,
+            None => break,
+        }
+        rest = &rest[1..];
+    }
+
+    if !seen_digit {
+        return None;
+    }
+
+    if let Some(after) = rest.strip_prefix(&exponent_chars[..]) {
+        let sign_len = after.starts_with(['+', '-']) as usize;
+        let digits = after[sign_len..]
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(after.len() - sign_len);
+        if digits > 0 {
+            let saturated = if after.starts_with('-') {
+                i32::MIN
+            } else {
+                i32::MAX
+            };
+            let exponent = after[..sign_len + digits].parse().unwrap_or(saturated);
+            number.exponent = number.exponent.saturating_add(exponent);
+            rest = &after[sign_len + digits..];
+        }
+    }
+
+    Some((number, rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(token: &str) -> Duration {
+        DurationSpec::parse(token).unwrap().to_duration()
+    }
+
+    #[test]
+    fn parse_with_nanosecond_precision() {
+        assert_eq!(Duration::from_micros(500), parse("0.0005"));
+        assert_eq!(Duration::from_millis(1), parse("1e-3"));
+        assert_eq!(Duration::from_millis(500), parse(".5s"));
+        assert_eq!(Duration::from_secs(6), parse("0.1m"));
+        assert_eq!(Duration::from_nanos(2), parse("1.5e-9"));
+        assert_eq!(Duration::from_nanos(1), parse("1e-30"));
+    }
+
+    #[test]
+    fn parse_hexadecimal_numbers() {
+        assert_eq!(Duration::from_secs(16), parse("0x10"));
+        assert_eq!(Duration::from_secs(29), parse("0x1d"));
+        assert_eq!(Duration::from_millis(3750), parse("0x1p-4m"));
+    }
+
+    #[test]
+    fn saturate_large_intervals() {
+        for token in &[
+            "inf",
+            "INFINITY",
+            "infd",
+            "1e400",
+            "99999999999999999999999d",
+            "0x1p99999",
+        ] {
+            assert_eq!(Duration::MAX, parse(token), "{}", token);
+        }
+    }
+
+    #[test]
+    fn reject_invalid_suffixes() {
+        for token in &[
+            "10sx", "1s1", "s", ".", "1e", "0x", "-1", "abc", "1 s", "nan", "infx",
+        ] {
+            assert!(DurationSpec::parse(token).is_err(), "{}", token);
+        }
+    }
+
+    #[test]
+    fn errors_point_to_offending_part() {
+        let err = DurationSpec::parse("10sx").unwrap_err();
+        assert_eq!((ErrorKind::InvalidUnit, 2..4), (err.kind(), err.span()));
+        assert_eq!("invalid time interval '10sx'", err.to_string());
+
+        let err = DurationSpec::parse("abc").unwrap_err();
+        assert_eq!((ErrorKind::InvalidNumber, 0..3), (err.kind(), err.span()));
+    }
+}
@@ -0,0 +1,39 @@
+//! Error messages in the words of the C library, as the GNU utilities print them.
+
+use std::ffi::CStr;
+use std::io;
+
+/// Describe an error like `strerror` does, so `No such file or directory` without the
+/// `(os error 2)` that Rust adds. Errors that don't come from the system keep their own message.
+pub fn describe(e: &io::Error) -> String {
+    let code = match e.raw_os_error() {
+        Some(code) => code,
+        None => return e.to_string(),
+    };
+
+    let mut message = [0; 256];
+    // SAFETY: the buffer is valid for its length, and strerror_r NUL-terminates it on success.
+    if unsafe { libc::strerror_r(code, message.as_mut_ptr(), message.len()) } != 0 {
+        return e.to_string();
+    }
+    // SAFETY: strerror_r succeeded.
+    unsafe { CStr::from_ptr(message.as_ptr()) }
+        .to_string_lossy()
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages_of_the_c_library() {
+        let describe_code = |code| describe(&io::Error::from_raw_os_error(code));
+        assert_eq!("No such file or directory", describe_code(libc::ENOENT));
+        assert_eq!("Permission denied", describe_code(libc::EACCES));
+        assert_eq!(
+            "a message (os error 1)",
+            describe(&io::Error::other("a message (os error 1)"))
+        );
+    }
+}
@@ -0,0 +1,10 @@
+//! Code that several utilities share, kept apart so that none of them has to depend on another.
+
+pub mod calendar;
+pub mod date;
+pub mod duration;
+pub mod error;
+pub mod localtime;
+
+/// Nanoseconds in a second, for times that are kept as seconds and nanoseconds.
+pub const NANOS_PER_SECOND: u32 = 1_000_000_000;
@@ -0,0 +1,63 @@
+//! Conversion between seconds since the epoch and the local time, following the rules of the
+//! time zone database including `TZ`.
+
+use std::convert::TryInto;
+use std::mem::MaybeUninit;
+
+/// A date and time of day, as shown on a wall clock.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DateTime {
+    pub year: i64,
+    /// Month from 1 to 12.
+    pub month: u32,
+    /// Day of the month from 1 to 31.
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+}
+
+/// Split seconds since the epoch into the local date and time.
+pub fn to_local(secs: i64) -> Option<DateTime> {
+    let secs = secs as libc::time_t;
+    let mut tm = MaybeUninit::uninit();
+    // SAFETY: both pointers are valid, and the result is initialized on success.
+    if unsafe { libc::localtime_r(&secs, tm.as_mut_ptr()) }.is_null() {
+        return None;
+    }
+    // SAFETY: localtime_r succeeded.
+    let tm = unsafe { tm.assume_init() };
+
+    Some(DateTime {
+        year: i64::from(tm.tm_year) + 1900,
+        month: (tm.tm_mon + 1) as u32,
+        day: tm.tm_mday as u32,
+        hour: tm.tm_hour as u32,
+        minute: tm.tm_min as u32,
+        second: tm.tm_sec as u32,
+    })
+}
+
+/// Convert a local date and time into seconds since the epoch. Returns `None` if there is no
+/// such time, like February 30 or a time skipped when daylight saving time starts.
+pub fn from_local(datetime: DateTime) -> Option<i64> {
+    // SAFETY: all fields of `tm` are integers or a nullable pointer, so zero is valid.
+    let mut tm: libc::tm = unsafe { MaybeUninit::zeroed().assume_init() };
+    tm.tm_year = (datetime.year - 1900).try_into().ok()?;
+    tm.tm_mon = datetime.month as libc::c_int - 1;
+    tm.tm_mday = datetime.day as libc::c_int;
+    tm.tm_hour = datetime.hour as libc::c_int;
+    tm.tm_min = datetime.minute as libc::c_int;
+    tm.tm_sec = datetime.second as libc::c_int;
+    // Let mktime find out whether daylight saving time is in effect.
+    tm.tm_isdst = -1;
+
+    // SAFETY: the pointer refers to a valid, initialized struct.
+    let secs = unsafe { libc::mktime(&mut tm) };
+
+    // mktime normalizes fields that are out of range, so a time that doesn't exist comes back
+    // different. This also tells a failure apart from the valid result of -1.
+    to_local(secs)
+        .filter(|normalized| *normalized == datetime)
+        .map(|_| secs)
+}
@@ -0,0 +1,70 @@
+use std::time::Duration;
+
+use common::duration::{self, DurationSpec, ErrorKind, Unit};
+use proptest::prelude::*;
+
+fn unit() -> impl Strategy<Value = Unit> {
+    prop::sample::select(Unit::ALL.to_vec())
+}
+
+proptest! {
+    #[test]
+    fn fractions_are_exact(secs in any::<u32>(), nanos in 0..1_000_000_000_u32) {
+        let spec = DurationSpec::parse(&format!("{}.{:09}", secs, nanos)).unwrap();
+        prop_assert_eq!(Duration::new(secs.into(), nanos), spec.to_duration());
+    }
+
+    #[test]
+    fn suffix_scales_by_unit(value in any::<u32>(), unit in unit()) {
+        let spec = DurationSpec::parse(&format!("{}{}", value, unit.suffix())).unwrap();
+        prop_assert_eq!(Some(unit), spec.explicit_unit());
+        prop_assert_eq!(
+            Duration::from_secs(u64::from(value) * unit.seconds() as u64),
+            spec.to_duration()
+        );
+    }
+
+    #[test]
+    fn missing_suffix_means_seconds(value in any::<u32>()) {
+        let spec = DurationSpec::parse(&value.to_string()).unwrap();
+        prop_assert_eq!(None, spec.explicit_unit());
+        prop_assert_eq!(Unit::Seconds, spec.unit());
+        prop_assert_eq!(DurationSpec::parse(&format!("{}s", value)).unwrap().to_duration(), spec.to_duration());
+    }
+
+    #[test]
+    fn total_is_sum_of_tokens(millis in prop::collection::vec(0..10_000_000_u64, 1..8)) {
+        let specs: Vec<DurationSpec> = millis
+            .iter()
+            .map(|ms| format!("{}.{:03}", ms / 1000, ms % 1000).parse().unwrap())
+            .collect();
+        prop_assert_eq!(
+            Duration::from_millis(millis.iter().sum()),
+            duration::total(&specs)
+        );
+    }
+
+    #[test]
+    fn overflow_saturates(digits in "[1-9][0-9]{20,40}", unit in unit(), exponent in 20..100_000_i32) {
+        let large = DurationSpec::parse(&format!("{}{}", digits, unit.suffix())).unwrap();
+        prop_assert_eq!(Duration::MAX, large.to_duration());
+
+        let scientific = DurationSpec::parse(&format!("1e{}", exponent)).unwrap();
+        prop_assert_eq!(Duration::MAX, scientific.to_duration());
+
+        let one = DurationSpec::parse("1").unwrap();
+        prop_assert_eq!(Duration::MAX, duration::total(&[large, one]));
+    }
+
+    #[test]
+    fn unknown_suffix_is_spanned(value in any::<u32>(), suffix in "[a-zA-Z ]{1,4}") {
+        prop_assume!(!["s", "m", "h", "d"].contains(&suffix.as_str()));
+
+        let number = value.to_string();
+        let token = format!("{}{}", number, suffix);
+        let err = DurationSpec::parse(&token).unwrap_err();
+        prop_assert_eq!(ErrorKind::InvalidUnit, err.kind());
+        prop_assert_eq!(number.len()..token.len(), err.span());
+        prop_assert_eq!(&token[err.span()], suffix.as_str());
+    }
+}
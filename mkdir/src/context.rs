@@ -0,0 +1,275 @@
+//! SELinux security contexts, which are kept in the `security.selinux` extended attribute of
+//! each file.
+
+use regex::Regex;
+use std::ffi::CString;
+use std::fs;
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+
+/// Whether SELinux is enabled, which is when its file system is mounted. That includes permissive
+/// mode, where labels are still kept but not enforced.
+pub fn enabled() -> bool {
+    Path::new("/sys/fs/selinux/enforce").exists()
+}
+
+/// Labels directories with a security context, either the one given or the one that the policy
+/// chooses for their path.
+pub struct Labeler {
+    attribute: CString,
+    context: Context,
+}
+
+enum Context {
+    Given(String),
+    Policy(Policy),
+}
+
+impl Labeler {
+    /// Label with the given context, like `--context=CTX`.
+    pub fn given(context: &str) -> Self {
+        Self::selinux(Context::Given(context.to_owned()))
+    }
+
+    /// Label with the context from the policy, like `-Z`. Returns `None` if the system has no
+    /// policy, which leaves the labels that the kernel chooses.
+    pub fn from_policy() -> Option<Self> {
+        Policy::load().map(|policy| Self::selinux(Context::Policy(policy)))
+    }
+
+    fn selinux(context: Context) -> Self {
+        Self {
+            attribute: CString::new("security.selinux").unwrap(),
+            context,
+        }
+    }
+
+    /// Set the security context of `directory`, which has to exist.
+    pub fn label(&self, directory: &Path) -> io::Result<()> {
+        let context = match &self.context {
+            Context::Given(context) => context.as_str(),
+            Context::Policy(policy) => {
+                let path = fs::canonicalize(directory)?;
+                match policy.lookup(&path.to_string_lossy()) {
+                    Some(context) => context,
+                    None => return Ok(()),
+                }
+            }
+        };
+
+        let path = CString::new(directory.as_os_str().as_bytes())?;
+        // The C library stores the context with its terminating NUL, so do the same.
+        let value = CString::new(context)?;
+        let value = value.as_bytes_with_nul();
+        // SAFETY: all pointers are valid for the given lengths.
+        let result = unsafe {
+            libc::lsetxattr(
+                path.as_ptr(),
+                self.attribute.as_ptr(),
+                value.as_ptr().cast(),
+                value.len(),
+                0,
+            )
+        };
+        if result != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+/// The contexts that the policy gives to files, from its `file_contexts` files.
+struct Policy {
+    specs: Vec<Spec>,
+}
+
+/// A line of `file_contexts` like `/home/[^/]+/\.ssh(/.*)? system_u:object_r:ssh_home_t:s0`.
+struct Spec {
+    regex: Regex,
+    /// Whether the path is matched as it is, without any special characters.
+    exact: bool,
+    /// The context, or `None` for `<<none>>` which means not to label at all.
+    context: Option<String>,
+}
+
+impl Policy {
+    /// Load the policy named in `/etc/selinux/config`, along with the local changes to it.
+    fn load() -> Option<Self> {
+        let config = fs::read_to_string("/etc/selinux/config").ok()?;
+        let name = config
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("SELINUXTYPE="))?;
+
+        let files = format!("/etc/selinux/{}/contexts/files/file_contexts", name);
+        let mut contents = fs::read_to_string(&files).ok()?;
+        for extra in &[".homedirs", ".local"] {
+            if let Ok(extra) = fs::read_to_string(files.clone() + extra) {
+                contents.push('\n');
+                contents.push_str(&extra);
+            }
+        }
+        Some(Self::parse(&contents))
+    }
+
+    /// Parse the lines of `file_contexts` that apply to directories. Lines that are malformed
+    /// are skipped.
+    fn parse(contents: &str) -> Self {
+        let specs = contents
+            .lines()
+            .filter_map(|line| {
+                let fields = line.split_whitespace().collect::<Vec<_>>();
+                let (pattern, context) = match fields[..] {
+                    [pattern, ..] if pattern.starts_with('#') => return None,
+                    [pattern, context] => (pattern, context),
+                    [pattern, "-d", context] => (pattern, context),
+                    _ => return None,
+                };
+
+                Some(Spec {
+                    regex: Regex::new(&format!("^(?:{})$", pattern)).ok()?,
+                    exact: !has_special_characters(pattern),
+                    context: Some(context)
+                        .filter(|&c| c != "<<none>>")
+                        .map(str::to_owned),
+                })
+            })
+            .collect();
+        Self { specs }
+    }
+
+    /// Find the context for the directory at `path`, which has to be absolute. Like in
+    /// libselinux, exact paths come first, and otherwise later lines override earlier ones.
+    fn lookup(&self, path: &str) -> Option<&str> {
+        let (exact, patterns): (Vec<_>, Vec<_>) = self.specs.iter().partition(|spec| spec.exact);
+        exact
+            .iter()
+            .rev()
+            .chain(patterns.iter().rev())
+            .find(|spec| spec.regex.is_match(path))
+            .and_then(|spec| spec.context.as_deref())
+    }
+}
+
+/// Whether a pattern has any characters with a special meaning in a regular expression.
+fn has_special_characters(pattern: &str) -> bool {
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '.' | '^' | '$' | '?' | '*' | '+' | '|' | '[' | '(' | '{' => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE_CONTEXTS: &str = r"
+# comments and other file types are skipped
+/.*                 system_u:object_r:default_t:s0
+/srv(/.*)?          system_u:object_r:var_t:s0
+/srv/www(/.*)?  -d  system_u:object_r:httpd_sys_content_t:s0
+/srv/www/logs   --  system_u:object_r:httpd_log_t:s0
+/srv/www/tmp        <<none>>
+/srv/[^/]+/data     system_u:object_r:data_t:s0
+/srv/www/data       system_u:object_r:www_data_t:s0
+";
+
+    #[test]
+    fn later_and_exact_lines_win() {
+        let policy = Policy::parse(FILE_CONTEXTS);
+        let lookup = |path| policy.lookup(path);
+
+        assert_eq!(Some("system_u:object_r:default_t:s0"), lookup("/home/x"));
+        assert_eq!(Some("system_u:object_r:var_t:s0"), lookup("/srv"));
+        assert_eq!(
+            Some("system_u:object_r:httpd_sys_content_t:s0"),
+            lookup("/srv/www/logs")
+        );
+        assert_eq!(None, lookup("/srv/www/tmp"));
+        assert_eq!(Some("system_u:object_r:data_t:s0"), lookup("/srv/ftp/data"));
+        assert_eq!(
+            Some("system_u:object_r:www_data_t:s0"),
+            lookup("/srv/www/data")
+        );
+    }
+
+    #[test]
+    fn special_characters() {
+        assert!(!has_special_characters("/srv/www"));
+        assert!(!has_special_characters(r"/srv/www\.d"));
+        assert!(has_special_characters("/srv(/.*)?"));
+        assert!(has_special_characters("/srv/[^/]+"));
+    }
+
+    /// Label in the `user` namespace, which tmpfs supports without SELinux.
+    fn user_labeler(context: Context) -> Labeler {
+        Labeler {
+            attribute: CString::new("user.selinux").unwrap(),
+            context,
+        }
+    }
+
+    fn read_label(path: &Path) -> Vec<u8> {
+        let path = CString::new(path.as_os_str().as_bytes()).unwrap();
+        let name = CString::new("user.selinux").unwrap();
+        let mut value = vec![0; 256];
+        // SAFETY: all pointers are valid for the given lengths.
+        let length = unsafe {
+            libc::lgetxattr(
+                path.as_ptr(),
+                name.as_ptr(),
+                value.as_mut_ptr().cast(),
+                value.len(),
+            )
+        };
+        assert!(length >= 0, "{}", io::Error::last_os_error());
+        value.truncate(length as usize);
+        value
+    }
+
+    #[test]
+    fn writes_the_extended_attribute() {
+        // tmpfs supports user extended attributes, but /dev/shm may be missing or something else,
+        // so the directory can be overridden.
+        let parent = std::env::var_os("MKDIR_XATTR_DIR").unwrap_or_else(|| "/dev/shm".into());
+        let dir = match tempfile::tempdir_in(&parent) {
+            Ok(dir) => dir,
+            Err(e) => {
+                eprintln!("skipped, can't use {:?}: {}", parent, e);
+                return;
+            }
+        };
+
+        let given = dir.path().join("given");
+        fs::create_dir(&given).unwrap();
+        let result =
+            user_labeler(Context::Given("system_u:object_r:tmp_t:s0".to_owned())).label(&given);
+        if let Err(e) = &result {
+            if e.raw_os_error() == Some(libc::ENOTSUP) {
+                eprintln!("skipped, {:?} has no user extended attributes", parent);
+                return;
+            }
+        }
+        result.unwrap();
+        assert_eq!(b"system_u:object_r:tmp_t:s0\0", &read_label(&given)[..]);
+
+        let pattern = format!("{}/from-.*", dir.path().display());
+        let policy = Policy::parse(&format!("{} -d system_u:object_r:var_t:s0", pattern));
+        let from_policy = dir.path().join("from-policy");
+        fs::create_dir(&from_policy).unwrap();
+        user_labeler(Context::Policy(policy))
+            .label(&from_policy)
+            .unwrap();
+        assert_eq!(
+            b"system_u:object_r:var_t:s0\0",
+            &read_label(&from_policy)[..]
+        );
+    }
+}
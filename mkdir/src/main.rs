@@ -1,6 +1,15 @@
-use std::{fs, os::unix::fs::PermissionsExt};
-use clap::{load_yaml, App};
+mod context;
+mod mode;
 
+use clap::{load_yaml, App, Arg};
+use common::error::describe;
+use context::Labeler;
+use mode::Mode;
+use std::fs::{self, DirBuilder, Permissions};
+use std::io;
+use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+use std::path::Path;
+use std::process;
 
 fn log<S: Into<String>>(msg: S) {
     println!("mkdir: {}", msg.into());
@@ -10,42 +19,197 @@ fn log_err<S: Into<String>>(msg: S) {
     eprintln!("mkdir: {}", msg.into());
 }
 
+/// Read the umask of the process, which can only be done by setting it.
+fn umask() -> u32 {
+    // SAFETY: umask can't fail, and the old mask is put back right away.
+    unsafe {
+        let mask = libc::umask(0);
+        libc::umask(mask);
+        mask as u32
+    }
+}
+
+/// Command line options shared by all directories.
+struct Options {
+    /// The mode given with -m, already applied to a=rwx.
+    mode: Option<u32>,
+    parents: bool,
+    verbose: bool,
+    umask: u32,
+    labeler: Option<Labeler>,
+}
+
 fn main() {
     let yaml = load_yaml!("mkdir.yml");
-    let matches = App::from_yaml(yaml).get_matches();
+    // The YAML loader of clap doesn't know about require_equals, which an optional value needs.
+    let matches = App::from_yaml(yaml)
+        .arg(
+            Arg::with_name("context")
+                .help(
+                    "Like -Z, or if CTX is specified then set the SELinux security context to CTX",
+                )
+                .long("context")
+                .value_name("CTX")
+                .min_values(0)
+                .require_equals(true),
+        )
+        .get_matches();
 
     let directories = matches.values_of("DIRECTORY").unwrap();
-    let verbose = matches.is_present("verbose");
-    let parents = matches.is_present("parents");
-    let has_mode = matches.is_present("mode");
+    let umask = umask();
 
-    let mkdir = {
-        if parents {
-            fs::create_dir_all
-        } else {
-            fs::create_dir
+    // Symbolic modes start from a=rwx, and the result replaces the umask entirely.
+    let mode = matches
+        .value_of("mode")
+        .map(|mode| match Mode::parse(mode) {
+            Some(parsed) => parsed.apply(0o777, true, umask),
+            None => {
+                log_err(format!("invalid mode '{}'", mode));
+                process::exit(1);
+            }
+        });
+
+    // Like GNU mkdir, asking for the default context is quietly ignored without SELinux, but a
+    // given context is worth a warning.
+    let labeler = match matches.value_of("context") {
+        Some(context) if context::enabled() => Some(Labeler::given(context)),
+        Some(_) => {
+            log_err("warning: ignoring --context; it requires an SELinux/SMACK-enabled kernel");
+            None
+        }
+        None if (matches.is_present("Z") || matches.is_present("context"))
+            && context::enabled() =>
+        {
+            Labeler::from_policy()
         }
+        None => None,
+    };
+
+    let options = Options {
+        mode,
+        parents: matches.is_present("parents"),
+        verbose: matches.is_present("verbose"),
+        umask,
+        labeler,
     };
+    let mut failed = false;
 
     for d in directories {
-        match mkdir(d) {
-            Ok(_) => {
-                if verbose { log(format!("created directory '{}'", d)) };
-                if has_mode {
-                    let mode = matches.value_of("mode").unwrap();
-                    match fs::metadata(d) {
-                        Ok(v) => {
-                            let mut perms = v.permissions();
-                            let umode: u32 = mode.parse().unwrap();
-                            perms.set_mode(umode);
-                        }
-                        Err(e) => {
-                            log_err(format!("{}", e));
-                        }
-                    }
-                }
+        let created = if options.parents {
+            make_ancestors(d, &options).and_then(|()| make_directory(d, &options))
+        } else {
+            make_directory(d, &options)
+        };
+
+        if let Err(e) = created {
+            log_err(e);
+            failed = true;
+        }
+    }
+
+    if failed {
+        process::exit(1);
+    }
+}
+
+/// Create `directory` with the given mode, or a=rwx minus the umask without one. With -p an
+/// existing directory is left as it is.
+fn make_directory(directory: &str, options: &Options) -> Result<(), String> {
+    let error = |e: io::Error| format!("cannot create directory '{}': {}", directory, describe(&e));
+
+    // The directory is never created with more permissions than asked for, but the umask
+    // may take some away, and mkdir ignores the special bits.
+    match DirBuilder::new()
+        .mode(options.mode.unwrap_or(0o777))
+        .create(directory)
+    {
+        Ok(()) => {}
+        Err(e) if options.parents && e.kind() == io::ErrorKind::AlreadyExists => {
+            if Path::new(directory).is_dir() {
+                return Ok(());
             }
-            Err(e) => log_err(format!("cannot create directory '{}': {}", d, e))
+            return Err(error(e));
         }
+        Err(e) => return Err(error(e)),
+    }
+    if let Some(mode) = options.mode {
+        fs::set_permissions(directory, Permissions::from_mode(mode)).map_err(error)?;
+    }
+
+    created(directory, options)
+}
+
+/// Create the missing directories leading to `directory`. Like with `mkdir -m u+wx`, they are
+/// always writable and searchable by the owner, so that the next one can be created inside.
+fn make_ancestors(directory: &str, options: &Options) -> Result<(), String> {
+    let mode = 0o777 & !options.umask | 0o300;
+
+    for ancestor in ancestors(directory) {
+        let error =
+            |e: io::Error| format!("cannot create directory '{}': {}", ancestor, describe(&e));
+
+        match DirBuilder::new().mode(mode).create(ancestor) {
+            Ok(()) => {}
+            // Something that isn't a directory can't have any inside, but a dangling symlink
+            // just exists, like GNU reports it.
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => match fs::metadata(ancestor) {
+                Ok(metadata) if metadata.is_dir() => continue,
+                Ok(_) => return Err(error(io::Error::from_raw_os_error(libc::ENOTDIR))),
+                Err(_) => return Err(error(e)),
+            },
+            Err(e) => return Err(error(e)),
+        }
+        if mode & options.umask != 0 {
+            fs::set_permissions(ancestor, Permissions::from_mode(mode)).map_err(error)?;
+        }
+
+        created(ancestor, options)?;
+    }
+    Ok(())
+}
+
+/// Finish a directory that was just created by labeling it and reporting it with -v.
+fn created(directory: &str, options: &Options) -> Result<(), String> {
+    if let Some(labeler) = &options.labeler {
+        labeler.label(Path::new(directory)).map_err(|e| {
+            format!(
+                "failed to set the security context of '{}': {}",
+                directory,
+                describe(&e)
+            )
+        })?;
+    }
+
+    if options.verbose {
+        log(format!("created directory '{}'", directory));
+    }
+    Ok(())
+}
+
+/// The leading parts of `path` that name its ancestors, spelled as in `path` so that messages
+/// show what was given. The root directory is left out.
+fn ancestors(path: &str) -> impl Iterator<Item = &str> {
+    path.match_indices('/')
+        .map(move |(end, _)| (&path[..end], &path[end..]))
+        .filter(|(ancestor, rest)| {
+            !ancestor.is_empty()
+                && !ancestor.ends_with('/')
+                && !rest.trim_start_matches('/').is_empty()
+        })
+        .map(|(ancestor, _)| ancestor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ancestors_keep_the_spelling() {
+        let all = |path| ancestors(path).collect::<Vec<_>>();
+        assert_eq!(vec!["a", "a/b"], all("a/b/c"));
+        assert_eq!(vec![".", "./x", "./x//y"], all("./x//y/z/"));
+        assert_eq!(vec!["/tmp", "/tmp/abs"], all("/tmp/abs/q"));
+        assert_eq!(Vec::<&str>::new(), all("/a//"));
+        assert_eq!(vec!["nope", "nope/.."], all("nope/../g"));
     }
 }
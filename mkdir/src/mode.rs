@@ -0,0 +1,249 @@
+//! File modes as given to chmod, either in octal like `755` or symbolic like `u=rwx,g+rx,o-w`.
+
+/// The bits a mode can change: the permissions along with set-user-ID, set-group-ID and sticky.
+const MODE_BITS: u32 = 0o7777;
+
+const SET_ID_BITS: u32 = 0o6000;
+const EXECUTE_BITS: u32 = 0o111;
+
+/// A parsed mode, which can be applied to the mode of an existing or new file.
+#[derive(Debug, PartialEq)]
+pub enum Mode {
+    /// An octal number that replaces the whole mode.
+    Octal(u32),
+    /// Comma-separated clauses that change the mode one after the other.
+    Symbolic(Vec<Clause>),
+}
+
+/// A symbolic clause like `ug+rw-x`.
+#[derive(Debug, PartialEq)]
+pub struct Clause {
+    /// The bits of the users that are affected, or 0 if none were given. Unlike `a`, that leaves
+    /// out the bits that are set in the umask.
+    who: u32,
+    actions: Vec<Action>,
+}
+
+#[derive(Debug, PartialEq)]
+struct Action {
+    op: Op,
+    perm: Perm,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Op {
+    Add,
+    Remove,
+    Set,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Perm {
+    /// Permissions from `rwxst`. `X` is execute permission, but only for directories and files
+    /// that are already executable by someone.
+    Bits {
+        bits: u32,
+        conditional_execute: bool,
+    },
+    /// The permissions that the given users currently have, as in `g=u`.
+    Copy(u32),
+}
+
+impl Mode {
+    /// Parse a mode, returning `None` if it is malformed.
+    pub fn parse(mode: &str) -> Option<Self> {
+        if !mode.is_empty() && mode.bytes().all(|c| (b'0'..=b'7').contains(&c)) {
+            return u32::from_str_radix(mode, 8)
+                .ok()
+                .filter(|&bits| bits <= MODE_BITS)
+                .map(Mode::Octal);
+        }
+
+        mode.split(',')
+            .map(parse_clause)
+            .collect::<Option<_>>()
+            .map(Mode::Symbolic)
+    }
+
+    /// Apply the mode to the current mode of a file. Clauses that don't say which users they
+    /// affect leave the bits of `umask` alone.
+    pub fn apply(&self, mode: u32, directory: bool, umask: u32) -> u32 {
+        let clauses = match self {
+            Mode::Octal(bits) => return *bits,
+            Mode::Symbolic(clauses) => clauses,
+        };
+
+        let mut mode = mode & MODE_BITS;
+        for clause in clauses {
+            let (affected, masked) = match clause.who {
+                0 => (MODE_BITS, umask),
+                who => (who, 0),
+            };
+            // Like chmod, `=` doesn't clear the set-ID bits of directories unless asked to.
+            let kept = match (directory, clause.who) {
+                (true, who) => SET_ID_BITS & !who,
+                (false, _) => 0,
+            };
+
+            for action in &clause.actions {
+                let value = match action.perm {
+                    Perm::Bits {
+                        bits,
+                        conditional_execute,
+                    } => {
+                        let execute =
+                            conditional_execute && (directory || mode & EXECUTE_BITS != 0);
+                        bits | if execute { EXECUTE_BITS } else { 0 }
+                    }
+                    Perm::Copy(users) => {
+                        let bits = mode & users;
+                        // Spread the read, write and execute bits over all users.
+                        [0o444, 0o222, 0o111]
+                            .iter()
+                            .filter(|&&same| bits & same != 0)
+                            .fold(0, |value, same| value | same)
+                    }
+                } & affected
+                    & !masked;
+
+                mode = match action.op {
+                    Op::Add => mode | value,
+                    Op::Remove => mode & !value,
+                    Op::Set => mode & (!affected | kept) | value,
+                };
+            }
+        }
+        mode
+    }
+}
+
+/// Parse a clause like `ug+rw-x`, made of the users followed by one or more actions.
+fn parse_clause(clause: &str) -> Option<Clause> {
+    let start = clause.find(|c| !"ugoa".contains(c))?;
+    let who = clause[..start]
+        .chars()
+        .map(users)
+        .fold(0, |who, bits| who | bits);
+
+    let mut actions = Vec::new();
+    let mut rest = &clause[start..];
+    while let Some(c) = rest.chars().next() {
+        let op = match c {
+            '+' => Op::Add,
+            '-' => Op::Remove,
+            '=' => Op::Set,
+            _ => return None,
+        };
+        rest = &rest[1..];
+
+        let end = rest.find(['+', '-', '=']).unwrap_or(rest.len());
+        let perm = match &rest[..end] {
+            source @ ("u" | "g" | "o") => Perm::Copy(users(source.chars().next()?)),
+            perms => {
+                let mut bits = 0;
+                let mut conditional_execute = false;
+                for perm in perms.chars() {
+                    match perm {
+                        'r' => bits |= 0o444,
+                        'w' => bits |= 0o222,
+                        'x' => bits |= EXECUTE_BITS,
+                        'X' => conditional_execute = true,
+                        's' => bits |= SET_ID_BITS,
+                        't' => bits |= 0o1000,
+                        _ => return None,
+                    }
+                }
+                Perm::Bits {
+                    bits,
+                    conditional_execute,
+                }
+            }
+        };
+        actions.push(Action { op, perm });
+        rest = &rest[end..];
+    }
+
+    if actions.is_empty() {
+        return None;
+    }
+    Some(Clause { who, actions })
+}
+
+/// The bits that belong to the users named by `u`, `g`, `o` or `a`.
+fn users(who: char) -> u32 {
+    match who {
+        'u' => 0o4700,
+        'g' => 0o2070,
+        'o' => 0o1007,
+        _ => MODE_BITS,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The mode of a new directory created with the given mode, like `mkdir -m` does.
+    fn directory(mode: &str, umask: u32) -> Option<u32> {
+        Mode::parse(mode).map(|mode| mode.apply(0o777, true, umask))
+    }
+
+    #[test]
+    fn octal_modes() {
+        assert_eq!(Some(0o700), directory("700", 0o022));
+        assert_eq!(Some(0o755), directory("0755", 0o077));
+        assert_eq!(Some(0o7777), directory("7777", 0o022));
+        assert_eq!(Some(0), directory("0", 0o022));
+        assert_eq!(None, directory("77777", 0o022));
+        assert_eq!(None, directory("8", 0o022));
+    }
+
+    #[test]
+    fn symbolic_modes() {
+        // Compared with GNU mkdir -m
+        let cases = [
+            ("u=rwx", 0o022, 0o777),
+            ("u=rwx,g+rx,o-w", 0o022, 0o775),
+            ("a=", 0o022, 0),
+            ("=r", 0o022, 0o444),
+            ("=r", 0o077, 0o400),
+            ("-w", 0o022, 0o577),
+            ("go=", 0o022, 0o700),
+            ("u+s", 0o022, 0o4777),
+            ("+s", 0o022, 0o6777),
+            ("=rwxst", 0o027, 0o7750),
+            ("g=u-w", 0o022, 0o757),
+            ("a-x,u+X", 0o022, 0o766),
+            ("ug=rw,o=r", 0o077, 0o664),
+            ("u-r+x", 0o022, 0o377),
+            ("go-rwx,u=rwx", 0o022, 0o700),
+        ];
+
+        for (mode, umask, expected) in cases {
+            assert_eq!(Some(expected), directory(mode, umask), "{}", mode);
+        }
+    }
+
+    #[test]
+    fn copy_and_conditional_execute_on_files() {
+        let apply = |mode: &str, old| Mode::parse(mode).unwrap().apply(old, false, 0o022);
+        assert_eq!(0o640, apply("g=u-w,o=", 0o644));
+        assert_eq!(0o755, apply("a+X", 0o744));
+        assert_eq!(0o644, apply("a+X", 0o644));
+        // The set-ID bits of files are cleared by `=`, but kept for directories.
+        assert_eq!(0o644, apply("u=rw", 0o4744));
+        assert_eq!(
+            0o4755,
+            Mode::parse("=rx,u+w").unwrap().apply(0o4700, true, 0)
+        );
+    }
+
+    #[test]
+    fn reject_invalid_modes() {
+        for mode in &[
+            "", "x", "u", "u=z", ",u+r", "u+r,", "u+r,,g+w", "a+ug", "+9",
+        ] {
+            assert_eq!(None, Mode::parse(mode), "{}", mode);
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use std::fs::File;
+use std::os::unix::fs::symlink;
+use std::path::Path;
+use std::process::{Command, Output};
+
+use tempfile::TempDir;
+
+/// Run mkdir inside the directory, so that the messages show the relative paths.
+fn mkdir(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_mkdir"))
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn parents_verbose_reports_every_directory() {
+    let dir = TempDir::new().unwrap();
+
+    let output = mkdir(dir.path(), &["-p", "-v", "a/b/c", "a/d"]);
+    assert_eq!(Some(0), output.status.code());
+    assert_eq!(
+        "mkdir: created directory 'a'\n\
+         mkdir: created directory 'a/b'\n\
+         mkdir: created directory 'a/b/c'\n\
+         mkdir: created directory 'a/d'\n",
+        stdout(&output)
+    );
+    assert!(dir.path().join("a/b/c").is_dir());
+
+    // Directories that exist already aren't reported.
+    let output = mkdir(dir.path(), &["-p", "-v", "a/b/c", "./a//e/"]);
+    assert_eq!(Some(0), output.status.code());
+    assert_eq!("mkdir: created directory './a//e/'\n", stdout(&output));
+    assert_eq!("", stderr(&output));
+}
+
+#[test]
+fn parents_through_symlinks() {
+    let dir = TempDir::new().unwrap();
+    std::fs::create_dir(dir.path().join("d")).unwrap();
+    symlink("d", dir.path().join("link")).unwrap();
+
+    let output = mkdir(dir.path(), &["-p", "-v", "link/x"]);
+    assert_eq!(Some(0), output.status.code());
+    assert_eq!("mkdir: created directory 'link/x'\n", stdout(&output));
+    assert!(dir.path().join("d/x").is_dir());
+}
+
+#[test]
+fn parents_that_are_no_directories() {
+    let dir = TempDir::new().unwrap();
+    File::create(dir.path().join("file")).unwrap();
+    symlink("file", dir.path().join("to-file")).unwrap();
+    symlink("nowhere", dir.path().join("dangling")).unwrap();
+
+    for (path, message) in &[
+        ("file/x", "cannot create directory 'file': Not a directory"),
+        (
+            "to-file/x",
+            "cannot create directory 'to-file': Not a directory",
+        ),
+        // Like GNU, a dangling symlink isn't followed to create its target.
+        (
+            "dangling/x",
+            "cannot create directory 'dangling': File exists",
+        ),
+    ] {
+        let output = mkdir(dir.path(), &["-p", path]);
+        assert_eq!(Some(1), output.status.code(), "{}", path);
+        assert_eq!(format!("mkdir: {}\n", message), stderr(&output), "{}", path);
+    }
+    assert!(!dir.path().join("nowhere").exists());
+}
@@ -0,0 +1,52 @@
+//! Measure the output throughput of `seq` for large sequences, similar to what
+//! `seq 1 100000000 > ids.txt` does. The binary is run as separate process, so the numbers include
+//! the cost of writing to a pipe.
+//!
+//! Run with `cargo bench`.
+
+use std::io::{self, Read};
+use std::process::{Command, Stdio};
+use std::time::Instant;
+
+fn main() -> io::Result<()> {
+    let cases: &[&[&str]] = &[
+        &["1", "100000000"],
+        &["18446744073709551615", "18446744073809551615"],
+        &["-s", ",", "1", "100000000"],
+        &["1", "7", "700000000"],
+        &["0", "0.5", "5000000"],
+    ];
+
+    let mut buf = vec![0; 1 << 20];
+
+    for args in cases {
+        let start = Instant::now();
+        let mut child = Command::new(env!("CARGO_BIN_EXE_seq"))
+            .args(*args)
+            .stdout(Stdio::piped())
+            .spawn()?;
+
+        let mut stdout = child.stdout.take().unwrap();
+        let mut total = 0;
+        loop {
+            match stdout.read(&mut buf)? {
+                0 => break,
+                n => total += n,
+            }
+        }
+
+        let status = child.wait()?;
+        assert!(status.success(), "seq {} failed", args.join(" "));
+
+        let elapsed = start.elapsed();
+        println!(
+            "seq {:<44} {:>6} MiB in {:>8.3} s ({:.2} GiB/s)",
+            args.join(" "),
+            total >> 20,
+            elapsed.as_secs_f64(),
+            total as f64 / elapsed.as_secs_f64() / f64::from(1 << 30)
+        );
+    }
+
+    Ok(())
+}
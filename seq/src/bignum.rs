@@ -0,0 +1,259 @@
+//! Unsigned integers of any size, as far as they are needed to convert floating point numbers from
+//! and to decimal without losing precision.
+
+use std::cmp::Ordering;
+
+/// An unsigned integer, made of 32-bit limbs with the least significant one first and no leading
+/// zero limbs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BigUint {
+    limbs: Vec<u32>,
+}
+
+impl BigUint {
+    pub fn zero() -> Self {
+        Self { limbs: Vec::new() }
+    }
+
+    pub fn from_u128(mut value: u128) -> Self {
+        let mut limbs = Vec::new();
+        while value > 0 {
+            limbs.push(value as u32);
+            value >>= 32;
+        }
+        Self { limbs }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    /// Amount of bits without leading zeros, which is 0 for zero itself.
+    pub fn bit_len(&self) -> u64 {
+        match self.limbs.last() {
+            Some(last) => self.limbs.len() as u64 * 32 - u64::from(last.leading_zeros()),
+            None => 0,
+        }
+    }
+
+    /// Convert to an `u128`, returning `None` if the number is too large.
+    pub fn to_u128(&self) -> Option<u128> {
+        if self.limbs.len() > 4 {
+            return None;
+        }
+        Some(
+            self.limbs
+                .iter()
+                .rev()
+                .fold(0, |value, &limb| value << 32 | u128::from(limb)),
+        )
+    }
+
+    /// Calculate `self * factor + summand`.
+    pub fn mul_add_small(&mut self, factor: u32, summand: u32) {
+        let mut carry = u64::from(summand);
+        for limb in &mut self.limbs {
+            let product = u64::from(*limb) * u64::from(factor) + carry;
+            *limb = product as u32;
+            carry = product >> 32;
+        }
+        if carry > 0 {
+            self.limbs.push(carry as u32);
+        }
+        self.trim();
+    }
+
+    /// Multiply with `10^exp`.
+    pub fn mul_pow10(&mut self, mut exp: u64) {
+        while exp > 0 {
+            let step = exp.min(9);
+            self.mul_add_small(10_u32.pow(step as u32), 0);
+            exp -= step;
+        }
+    }
+
+    /// Divide by a small divisor in place, returning the remainder.
+    fn div_rem_small(&mut self, divisor: u32) -> u32 {
+        let mut rem = 0_u64;
+        for limb in self.limbs.iter_mut().rev() {
+            let current = rem << 32 | u64::from(*limb);
+            *limb = (current / u64::from(divisor)) as u32;
+            rem = current % u64::from(divisor);
+        }
+        self.trim();
+        rem as u32
+    }
+
+    /// Check whether any of the lowest `bits` bits is set.
+    pub fn has_bits_below(&self, bits: u64) -> bool {
+        let full = (bits / 32) as usize;
+        if self.limbs.iter().take(full).any(|&limb| limb != 0) {
+            return true;
+        }
+        let partial = bits % 32;
+        partial > 0
+            && self
+                .limbs
+                .get(full)
+                .is_some_and(|&limb| limb << (32 - partial) != 0)
+    }
+
+    /// Check whether the bit at the given position is set.
+    pub fn bit(&self, pos: u64) -> bool {
+        self.limbs
+            .get((pos / 32) as usize)
+            .is_some_and(|&limb| limb >> (pos % 32) & 1 == 1)
+    }
+
+    pub fn shl(&self, bits: u64) -> Self {
+        if self.is_zero() {
+            return Self::zero();
+        }
+        let shift = (bits % 32) as u32;
+        let mut limbs = vec![0; (bits / 32) as usize];
+        let mut carry = 0;
+        for &limb in &self.limbs {
+            limbs.push(limb << shift | carry);
+            carry = if shift == 0 { 0 } else { limb >> (32 - shift) };
+        }
+        limbs.push(carry);
+        let mut result = Self { limbs };
+        result.trim();
+        result
+    }
+
+    pub fn shr(&self, bits: u64) -> Self {
+        let skip = (bits / 32) as usize;
+        if skip >= self.limbs.len() {
+            return Self::zero();
+        }
+        let shift = (bits % 32) as u32;
+        let limbs = &self.limbs[skip..];
+        let mut result = Self {
+            limbs: limbs
+                .iter()
+                .enumerate()
+                .map(|(i, &limb)| {
+                    let high = match limbs.get(i + 1) {
+                        Some(&next) if shift > 0 => next << (32 - shift),
+                        _ => 0,
+                    };
+                    limb >> shift | high
+                })
+                .collect(),
+        };
+        result.trim();
+        result
+    }
+
+    /// Subtract a number that must not be larger than this one.
+    fn sub_assign(&mut self, other: &Self) {
+        let mut borrow = 0_i64;
+        for (i, limb) in self.limbs.iter_mut().enumerate() {
+            let diff =
+                i64::from(*limb) - i64::from(other.limbs.get(i).copied().unwrap_or(0)) - borrow;
+            *limb = diff.rem_euclid(1 << 32) as u32;
+            borrow = (diff < 0) as i64;
+        }
+        self.trim();
+    }
+
+    /// Divide by another number, returning the quotient and the remainder. This goes bit by bit,
+    /// so it's only meant for quotients of a few hundred bits at most.
+    pub fn div_rem(&self, divisor: &Self) -> (Self, Self) {
+        let mut rem = self.clone();
+        let mut quotient = Self::zero();
+        if self.bit_len() < divisor.bit_len() {
+            return (quotient, rem);
+        }
+
+        for shift in (0..=self.bit_len() - divisor.bit_len()).rev() {
+            let shifted = divisor.shl(shift);
+            if rem >= shifted {
+                rem.sub_assign(&shifted);
+                quotient = quotient.set_bit(shift);
+            }
+        }
+        (quotient, rem)
+    }
+
+    fn set_bit(mut self, pos: u64) -> Self {
+        let index = (pos / 32) as usize;
+        if self.limbs.len() <= index {
+            self.limbs.resize(index + 1, 0);
+        }
+        self.limbs[index] |= 1 << (pos % 32);
+        self
+    }
+
+    /// Print the number in decimal.
+    pub fn to_decimal(&self) -> String {
+        let mut rest = self.clone();
+        let mut chunks = Vec::new();
+        while !rest.is_zero() {
+            chunks.push(rest.div_rem_small(1_000_000_000));
+        }
+
+        let mut digits = chunks.pop().unwrap_or(0).to_string();
+        for chunk in chunks.iter().rev() {
+            digits.push_str(&format!("{:09}", chunk));
+        }
+        digits
+    }
+
+    fn trim(&mut self) {
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(value: u128) -> BigUint {
+        BigUint::from_u128(value)
+    }
+
+    #[test]
+    fn shifts_and_bits() {
+        let value = big(0b1011).shl(100);
+        assert_eq!(104, value.bit_len());
+        assert!(value.bit(100) && !value.bit(102) && value.bit(103));
+        assert!(!value.has_bits_below(100));
+        assert!(value.has_bits_below(101));
+        assert_eq!(big(0b101), value.shr(101));
+        assert_eq!(Some(u128::MAX), big(u128::MAX).shl(7).shr(7).to_u128());
+    }
+
+    #[test]
+    fn division_and_decimal() {
+        let mut value = big(123);
+        value.mul_pow10(40);
+        assert_eq!(format!("123{}", "0".repeat(40)), value.to_decimal());
+
+        let (quotient, rem) = value.div_rem(&big(7));
+        assert_eq!(
+            "175714285714285714285714285714285714285714",
+            quotient.to_decimal()
+        );
+        assert_eq!(big(2), rem);
+        assert_eq!("0", BigUint::zero().to_decimal());
+    }
+}
@@ -0,0 +1,240 @@
+//! Exact decimal numbers, used to calculate the numbers of a sequence without the rounding errors
+//! that floating point arithmetic would introduce.
+
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+
+/// Highest scale that is supported, as `10^38` is the largest power of ten that fits into an
+/// `i128`.
+const MAX_SCALE: u32 = 38;
+
+/// A decimal number that is represented as `mantissa / 10^scale`.
+///
+/// The scale is kept exactly as it was written on the command line, so `1.50` has a scale of 2,
+/// which is also the amount of fractional digits it is printed with by default.
+#[derive(Clone, Copy, Debug)]
+pub struct Decimal {
+    mantissa: i128,
+    scale: u32,
+}
+
+impl Decimal {
+    /// Largest number that can be represented.
+    pub const MAX: Self = Self {
+        mantissa: i128::MAX,
+        scale: 0,
+    };
+    /// Smallest number that can be represented.
+    pub const MIN: Self = Self {
+        mantissa: i128::MIN,
+        scale: 0,
+    };
+
+    /// Parse a decimal number in the form `[+-]DIGITS[.DIGITS][(e|E)[+-]DIGITS]`. Returns `None` if
+    /// the input is in a different form or the number is too large to be represented exactly.
+    pub fn parse(s: &str) -> Option<Self> {
+        let (negative, s) = match s.as_bytes().first()? {
+            b'-' => (true, &s[1..]),
+            b'+' => (false, &s[1..]),
+            _ => (false, s),
+        };
+        let (number, exponent) = match s.find(['e', 'E']) {
+            Some(pos) => (&s[..pos], s[pos + 1..].parse::<i32>().ok()?),
+            None => (s, 0),
+        };
+        let (integer, fraction) = number.split_once('.').unwrap_or((number, ""));
+
+        if integer.is_empty() && fraction.is_empty() {
+            return None;
+        }
+
+        let mut mantissa = 0_i128;
+        for c in integer.bytes().chain(fraction.bytes()) {
+            if !c.is_ascii_digit() {
+                return None;
+            }
+            mantissa = mantissa
+                .checked_mul(10)?
+                .checked_add(i128::from(c - b'0'))?;
+        }
+
+        let scale = i64::try_from(fraction.len()).ok()? - i64::from(exponent);
+        let (mantissa, scale) = if scale < 0 {
+            (
+                mantissa.checked_mul(pow10(u32::try_from(-scale).ok()?)?)?,
+                0,
+            )
+        } else {
+            (mantissa, u32::try_from(scale).ok()?)
+        };
+
+        if scale > MAX_SCALE {
+            return None;
+        }
+
+        Some(Self {
+            mantissa: if negative { -mantissa } else { mantissa },
+            scale,
+        })
+    }
+
+    /// Add two numbers, returning `None` on overflow. The result has the larger scale of both.
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        let scale = self.scale.max(other.scale);
+        Some(Self {
+            mantissa: self
+                .rescale(scale)?
+                .mantissa
+                .checked_add(other.rescale(scale)?.mantissa)?,
+            scale,
+        })
+    }
+
+    /// Multiply the number by an integer factor, returning `None` on overflow.
+    pub fn checked_mul(self, factor: i128) -> Option<Self> {
+        Some(Self {
+            mantissa: self.mantissa.checked_mul(factor)?,
+            scale: self.scale,
+        })
+    }
+
+    /// Check whether `long double` arithmetic, which GNU `seq` uses, gives the same number when
+    /// printed at the number's scale. That is the case while the mantissa leaves a few bits of the
+    /// 64 bits of precision for the rounding errors of adding and multiplying.
+    pub fn fits_long_double(self) -> bool {
+        self.mantissa.unsigned_abs() <= 1 << 60
+    }
+
+    /// Check whether the number is less than zero.
+    pub fn is_negative(self) -> bool {
+        self.mantissa < 0
+    }
+
+    /// Print the absolute value in fixed point notation with `precision` fractional digits. A
+    /// smaller precision than the scale rounds half away from zero.
+    pub fn to_fixed(self, precision: usize) -> String {
+        let mut magnitude = self.mantissa.unsigned_abs();
+        let scale = self.scale as usize;
+
+        if precision < scale {
+            let divisor = 10_u128.pow((scale - precision) as u32);
+            magnitude = (magnitude + divisor / 2) / divisor;
+        }
+
+        let mut digits = magnitude.to_string();
+        let scale = scale.min(precision);
+        if digits.len() <= scale {
+            digits.insert_str(0, &"0".repeat(scale + 1 - digits.len()));
+        }
+        if precision > 0 {
+            digits.insert(digits.len() - scale, '.');
+            digits.push_str(&"0".repeat(precision - scale));
+        }
+        digits
+    }
+
+    /// Convert the number to a higher scale, returning `None` if the mantissa overflows.
+    fn rescale(self, scale: u32) -> Option<Self> {
+        Some(Self {
+            mantissa: self.mantissa.checked_mul(pow10(scale - self.scale)?)?,
+            scale,
+        })
+    }
+
+    /// Split the number into its integer part and its fractional digits at the given scale, which
+    /// must be greater or equal to the number's scale. Both parts carry the sign of the number.
+    fn split(self, scale: u32) -> (i128, i128) {
+        let factor = 10_i128.pow(self.scale);
+        let fraction = (self.mantissa % factor) * 10_i128.pow(scale - self.scale);
+        (self.mantissa / factor, fraction)
+    }
+}
+
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Decimal {}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Compare integer and fractional parts separately, so numbers of different scales can be
+        // compared without the risk of overflowing the mantissa.
+        let scale = self.scale.max(other.scale);
+        self.split(scale).cmp(&other.split(scale))
+    }
+}
+
+impl fmt::Display for Decimal {
+    /// Print the number in fixed point notation. The precision defaults to the scale of the number
+    /// and a smaller precision rounds half away from zero. Zero padding is placed after the sign,
+    /// like for any other number.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let precision = f.precision().unwrap_or(self.scale as usize);
+        f.pad_integral(self.mantissa >= 0, "", &self.to_fixed(precision))
+    }
+}
+
+/// Calculate `10^exp`, returning `None` if it doesn't fit into an `i128`.
+fn pow10(exp: u32) -> Option<i128> {
+    10_i128.checked_pow(exp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(s: &str) -> Decimal {
+        Decimal::parse(s).unwrap()
+    }
+
+    #[test]
+    fn parse_keeps_written_scale() {
+        assert_eq!("1.50", dec("1.50").to_string());
+        assert_eq!("-0.5", dec("-.5").to_string());
+        assert_eq!("5", dec("+5.").to_string());
+        assert_eq!("1500", dec("1.5e3").to_string());
+        assert_eq!("0.015", dec("1.5e-2").to_string());
+    }
+
+    #[test]
+    fn parse_rejects_invalid_input() {
+        for s in &[
+            "", "-", ".", "e3", "1e", "1.2.3", "0x10", "inf", "nan", "1e-39",
+        ] {
+            assert!(Decimal::parse(s).is_none(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn arithmetic_is_exact() {
+        let sum = (0..10).fold(dec("0"), |acc, _| acc.checked_add(dec("0.1")).unwrap());
+        assert_eq!(dec("1"), sum);
+        assert_eq!("0.3", dec("0.1").checked_mul(3).unwrap().to_string());
+    }
+
+    #[test]
+    fn ordering_across_scales() {
+        assert!(dec("1.05") < dec("1.1"));
+        assert!(dec("-1.05") > dec("-1.1"));
+        assert!(dec("-0.5") < dec("0"));
+        assert_eq!(dec("2.000"), dec("2"));
+    }
+
+    #[test]
+    fn format_with_precision_and_width() {
+        assert_eq!("1.000", format!("{:.3}", dec("1")));
+        assert_eq!("-01.50", format!("{:06.2}", dec("-1.5")));
+        assert_eq!("0.1", format!("{:.1}", dec("0.05")));
+        assert_eq!("-0", format!("{:.0}", dec("-0.4")));
+    }
+}
@@ -0,0 +1,645 @@
+//! Floating point operands, which are used whenever a number can't be represented as exact decimal,
+//! like hexadecimal or infinite values.
+//!
+//! GNU `seq` calculates these in `long double`, which is the 80-bit extended precision format of
+//! x86 with a 64-bit mantissa. Its results are emulated exactly here, including the rounding of
+//! every operation, so that numbers like `1e39` are printed with the same digits.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::bignum::BigUint;
+
+/// Bits of the mantissa, which includes the integer bit.
+const MANTISSA_BITS: i64 = 64;
+/// Exponent of the highest bit that a finite number can have.
+const MAX_EXPONENT: i64 = 16383;
+/// Exponent of the lowest bit of the smallest subnormal number.
+const MIN_EXPONENT: i64 = -16445;
+
+/// A number in the extended precision format.
+#[derive(Clone, Copy, Debug)]
+pub struct Float {
+    negative: bool,
+    kind: Kind,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Kind {
+    Zero,
+    /// The value `mantissa * 2^exponent`. The highest bit of the mantissa is set unless the number
+    /// is subnormal, so every value has exactly one representation.
+    Finite {
+        mantissa: u64,
+        exponent: i64,
+    },
+    Infinite,
+    Nan,
+}
+
+/// Parse a number in any of the forms that GNU `seq` accepts. That is a decimal number with an
+/// optional exponent like `1.5e3`, a hexadecimal number with an optional binary exponent like
+/// `0x1.8p-2`, or `inf`, `infinity` and `nan`, all with an optional sign. The result is rounded
+/// to the nearest representable number, like `strtold` does.
+pub fn parse(arg: &str) -> Option<Float> {
+    let (negative, unsigned) = match arg.as_bytes().first()? {
+        b'-' => (true, &arg[1..]),
+        b'+' => (false, &arg[1..]),
+        _ => (false, arg),
+    };
+
+    let kind = match unsigned.to_ascii_lowercase().as_str() {
+        "inf" | "infinity" => Kind::Infinite,
+        "nan" => Kind::Nan,
+        lower if lower.starts_with("0x") => parse_hex(&unsigned[2..])?,
+        _ => parse_decimal(unsigned)?,
+    };
+
+    Some(Float { negative, kind })
+}
+
+/// Split a number into its integer digits, fractional digits and the exponent after the given
+/// marker. The exponent saturates, as far larger or smaller numbers are out of range anyway.
+fn split_number(s: &str, marker: [char; 2]) -> Option<(&str, &str, i64)> {
+    let (number, exponent) = match s.find(marker) {
+        Some(pos) => (&s[..pos], parse_exponent(&s[pos + 1..])?),
+        None => (s, 0),
+    };
+    let (integer, fraction) = number.split_once('.').unwrap_or((number, ""));
+
+    if integer.is_empty() && fraction.is_empty() {
+        return None;
+    }
+    Some((integer, fraction, exponent))
+}
+
+fn parse_exponent(s: &str) -> Option<i64> {
+    let (negative, digits) = match s.as_bytes().first()? {
+        b'-' => (true, &s[1..]),
+        b'+' => (false, &s[1..]),
+        _ => (false, s),
+    };
+    if digits.is_empty() || !digits.bytes().all(|d| d.is_ascii_digit()) {
+        return None;
+    }
+
+    let value = digits.bytes().fold(0_i64, |value, d| {
+        value.saturating_mul(10).saturating_add(i64::from(d - b'0'))
+    });
+    Some(if negative { -value } else { value })
+}
+
+/// Parse a decimal number without sign, like `1.5e3`.
+fn parse_decimal(s: &str) -> Option<Kind> {
+    let (integer, fraction, exponent) = split_number(s, ['e', 'E'])?;
+    let digits = format!("{}{}", integer, fraction);
+    if !digits.bytes().all(|d| d.is_ascii_digit()) {
+        return None;
+    }
+
+    // Leading zeros don't matter and trailing ones only move the exponent.
+    let significant = digits.trim_start_matches('0');
+    let trimmed = significant.trim_end_matches('0');
+    let exponent = exponent
+        .saturating_sub(fraction.len() as i64)
+        .saturating_add((significant.len() - trimmed.len()) as i64);
+    if trimmed.is_empty() {
+        return Some(Kind::Zero);
+    }
+
+    // Numbers far out of range are decided early, which also keeps the powers of ten small.
+    let magnitude = exponent.saturating_add(trimmed.len() as i64);
+    if magnitude > 4934 {
+        return Some(Kind::Infinite);
+    } else if magnitude < -4952 {
+        return Some(Kind::Zero);
+    }
+
+    let mut mantissa = BigUint::zero();
+    for d in trimmed.bytes() {
+        mantissa.mul_add_small(10, u32::from(d - b'0'));
+    }
+
+    let mut divisor = BigUint::from_u128(1);
+    if exponent >= 0 {
+        mantissa.mul_pow10(exponent as u64);
+    } else {
+        divisor.mul_pow10(exponent.unsigned_abs());
+    }
+    Some(round_ratio(&mantissa, &divisor, 0))
+}
+
+/// Parse the part of a hexadecimal number after the `0x` prefix, like `1.8p-2`.
+fn parse_hex(s: &str) -> Option<Kind> {
+    let (integer, fraction, exponent) = split_number(s, ['p', 'P'])?;
+
+    let mut mantissa = BigUint::zero();
+    for c in integer.chars().chain(fraction.chars()) {
+        mantissa.mul_add_small(16, c.to_digit(16)?);
+    }
+    if mantissa.is_zero() {
+        return Some(Kind::Zero);
+    }
+
+    let exponent = exponent.saturating_sub(4 * fraction.len() as i64);
+    let magnitude = exponent.saturating_add(mantissa.bit_len() as i64);
+    if magnitude > MAX_EXPONENT + 1 {
+        return Some(Kind::Infinite);
+    } else if magnitude < MIN_EXPONENT - 1 {
+        return Some(Kind::Zero);
+    }
+    Some(round_ratio(&mantissa, &BigUint::from_u128(1), exponent))
+}
+
+/// Round the value `numerator / denominator * 2^exponent` to the nearest representable number.
+fn round_ratio(numerator: &BigUint, denominator: &BigUint, exponent: i64) -> Kind {
+    // Scale the quotient to 66 bits at least, which leaves two bits to round with.
+    let shift = 66 + denominator.bit_len() as i64 - numerator.bit_len() as i64;
+    let (quotient, rem) = if shift >= 0 {
+        numerator.shl(shift as u64).div_rem(denominator)
+    } else {
+        numerator.div_rem(&denominator.shl(shift.unsigned_abs()))
+    };
+    round(
+        quotient.to_u128().unwrap(),
+        !rem.is_zero(),
+        exponent - shift,
+    )
+}
+
+/// Round the value `(value + sticky) * 2^exponent` to the nearest representable number, with ties
+/// going to the even one. `sticky` stands for any further bits below the value, which decide the
+/// rounding if the value itself lies halfway between two numbers. If they are given, the value
+/// needs to have two bits more than the mantissa.
+fn round(value: u128, sticky: bool, exponent: i64) -> Kind {
+    if value == 0 {
+        return Kind::Zero;
+    }
+
+    let top = exponent + 127 - i64::from(value.leading_zeros());
+    let lowest = (top - (MANTISSA_BITS - 1)).max(MIN_EXPONENT);
+    let drop = lowest - exponent;
+
+    let mut mantissa = if drop <= 0 {
+        value << drop.unsigned_abs()
+    } else if drop > 128 {
+        0
+    } else {
+        let half = 1_u128 << (drop - 1);
+        let kept = value.checked_shr(drop as u32).unwrap_or(0);
+        let rest = value & (half - 1);
+        if value & half != 0 && (sticky || rest != 0 || kept & 1 == 1) {
+            kept + 1
+        } else {
+            kept
+        }
+    };
+
+    let mut lowest = lowest;
+    if mantissa >> MANTISSA_BITS != 0 {
+        mantissa >>= 1;
+        lowest += 1;
+    }
+    if mantissa == 0 {
+        return Kind::Zero;
+    }
+    if lowest + 127 - i64::from(mantissa.leading_zeros()) > MAX_EXPONENT {
+        return Kind::Infinite;
+    }
+    normalize(mantissa as u64, lowest)
+}
+
+/// Shift the mantissa up as far as the exponent allows.
+fn normalize(mantissa: u64, exponent: i64) -> Kind {
+    let shift = i64::from(mantissa.leading_zeros()).min(exponent - MIN_EXPONENT);
+    Kind::Finite {
+        mantissa: mantissa << shift,
+        exponent: exponent - shift,
+    }
+}
+
+impl Float {
+    /// Amount of significant digits.
+    const PRECISION: i64 = 6;
+
+    pub fn is_nan(&self) -> bool {
+        matches!(self.kind, Kind::Nan)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        matches!(self.kind, Kind::Zero)
+    }
+
+    pub fn is_finite(&self) -> bool {
+        matches!(self.kind, Kind::Zero | Kind::Finite { .. })
+    }
+
+    pub fn is_infinite(&self) -> bool {
+        matches!(self.kind, Kind::Infinite)
+    }
+
+    pub fn is_sign_negative(&self) -> bool {
+        self.negative
+    }
+
+    /// Add two numbers, rounding the exact sum.
+    pub fn add(self, other: Self) -> Self {
+        let (a, b) = match (self.finite(), other.finite()) {
+            (Some(a), Some(b)) => (a, b),
+            _ => return self.add_special(other),
+        };
+
+        // Align both at 63 bits below the larger one, where the smaller one may lose bits that
+        // only count for rounding.
+        let ((high, high_negative), (low, low_negative)) = if a.1 >= b.1 {
+            ((a, self.negative), (b, other.negative))
+        } else {
+            ((b, other.negative), (a, self.negative))
+        };
+        let exponent = high.1 - 63;
+        let high_value = u128::from(high.0) << 63;
+        let shift = low.1 - exponent;
+        let (low_value, sticky) = if shift >= 0 {
+            (u128::from(low.0) << shift, false)
+        } else if shift <= -64 {
+            (0, true)
+        } else {
+            let shift = shift.unsigned_abs() as u32;
+            let lost = u128::from(low.0) & ((1 << shift) - 1);
+            (u128::from(low.0) >> shift, lost != 0)
+        };
+
+        let (negative, value) = if high_negative == low_negative {
+            (high_negative, high_value + low_value)
+        } else if high_value > low_value {
+            // The lost bits make the difference a bit smaller, so it's one unit less followed by
+            // some bits.
+            (high_negative, high_value - low_value - sticky as u128)
+        } else {
+            (low_negative, low_value - high_value)
+        };
+
+        match round(value, sticky, exponent) {
+            // An exact zero is positive, like `x + -x` in IEEE arithmetic.
+            Kind::Zero => Self {
+                negative: false,
+                kind: Kind::Zero,
+            },
+            kind => Self { negative, kind },
+        }
+    }
+
+    /// Add two numbers of which at least one isn't finite or zero.
+    fn add_special(self, other: Self) -> Self {
+        match (self.kind, other.kind) {
+            (Kind::Nan, _) | (_, Kind::Nan) => NAN,
+            (Kind::Infinite, Kind::Infinite) if self.negative != other.negative => NAN,
+            (Kind::Infinite, _) => self,
+            (_, Kind::Infinite) => other,
+            (Kind::Zero, Kind::Zero) => Self {
+                negative: self.negative && other.negative,
+                kind: Kind::Zero,
+            },
+            (Kind::Zero, _) => other,
+            _ => self,
+        }
+    }
+
+    /// Multiply two numbers, rounding the exact product.
+    pub fn mul(self, other: Self) -> Self {
+        let negative = self.negative != other.negative;
+        let kind = match (self.kind, other.kind) {
+            (Kind::Nan, _) | (_, Kind::Nan) => Kind::Nan,
+            (Kind::Infinite, Kind::Zero) | (Kind::Zero, Kind::Infinite) => Kind::Nan,
+            (Kind::Infinite, _) | (_, Kind::Infinite) => Kind::Infinite,
+            (Kind::Zero, _) | (_, Kind::Zero) => Kind::Zero,
+            (
+                Kind::Finite { mantissa, exponent },
+                Kind::Finite {
+                    mantissa: other_mantissa,
+                    exponent: other_exponent,
+                },
+            ) => round(
+                u128::from(mantissa) * u128::from(other_mantissa),
+                false,
+                exponent + other_exponent,
+            ),
+        };
+        Self { negative, kind }
+    }
+
+    fn finite(&self) -> Option<(u64, i64)> {
+        match self.kind {
+            Kind::Finite { mantissa, exponent } => Some((mantissa, exponent)),
+            _ => None,
+        }
+    }
+
+    /// Calculate `|self| * 10^scale`, rounded to an integer with ties going to the even one, like
+    /// `printf` does.
+    fn scaled(&self, scale: i64) -> BigUint {
+        let (mantissa, exponent) = match self.finite() {
+            Some(finite) => finite,
+            None => return BigUint::zero(),
+        };
+
+        let mut numerator = BigUint::from_u128(mantissa.into());
+        if scale >= 0 {
+            numerator.mul_pow10(scale as u64);
+        }
+
+        let (mut quotient, half, sticky) = if scale >= 0 && exponent >= 0 {
+            (numerator.shl(exponent as u64), false, false)
+        } else if scale >= 0 {
+            // Dividing by a power of two is just a shift, which is much faster for the many
+            // digits that small numbers have.
+            let bits = exponent.unsigned_abs();
+            (
+                numerator.shr(bits),
+                numerator.bit(bits - 1),
+                numerator.has_bits_below(bits - 1),
+            )
+        } else {
+            let mut denominator = BigUint::from_u128(1);
+            denominator.mul_pow10(scale.unsigned_abs());
+            if exponent >= 0 {
+                numerator = numerator.shl(exponent as u64);
+            } else {
+                denominator = denominator.shl(exponent.unsigned_abs());
+            }
+            let (quotient, rem) = numerator.div_rem(&denominator);
+            let twice = rem.shl(1);
+            (quotient, twice >= denominator, twice > denominator)
+        };
+
+        if half && (sticky || quotient.bit(0)) {
+            quotient.mul_add_small(1, 1);
+        }
+        quotient
+    }
+
+    /// Print the absolute value in fixed point notation with `precision` fractional digits, like
+    /// the `%Lf` conversion.
+    pub fn to_fixed(self, precision: usize) -> String {
+        match self.kind {
+            Kind::Nan => return "nan".to_owned(),
+            Kind::Infinite => return "inf".to_owned(),
+            _ => {}
+        }
+
+        // Digits beyond those of the exact binary fraction are zeros.
+        let exponent = self.finite().map_or(0, |(_, exponent)| exponent);
+        let computed = precision.min(exponent.min(0).unsigned_abs() as usize);
+
+        let mut digits = self.scaled(computed as i64).to_decimal();
+        if digits.len() <= computed {
+            digits.insert_str(0, &"0".repeat(computed + 1 - digits.len()));
+        }
+        if precision > 0 {
+            digits.insert(digits.len() - computed, '.');
+            digits.push_str(&"0".repeat(precision - computed));
+        }
+        digits
+    }
+
+    /// Round the absolute value to the given amount of significant digits. Returns the digits and
+    /// the decimal exponent of the first one.
+    fn significant(&self, digits: i64) -> (String, i64) {
+        let (mantissa, exponent) = match self.finite() {
+            Some(finite) => finite,
+            None => return ("0".repeat(digits as usize), 0),
+        };
+
+        // Estimate the exponent from the binary one, and correct it if that was off by one.
+        let top = exponent + 63 - i64::from(mantissa.leading_zeros());
+        let mut decimal = (top as f64 * std::f64::consts::LOG10_2).floor() as i64;
+        loop {
+            let rounded = self.scaled(digits - 1 - decimal).to_decimal();
+            match rounded.len() as i64 - digits {
+                0 => return (rounded, decimal),
+                1 => decimal += 1,
+                _ => decimal -= 1,
+            }
+        }
+    }
+}
+
+impl From<f64> for Float {
+    /// Convert a double precision number, which is always exact.
+    fn from(value: f64) -> Self {
+        let negative = value.is_sign_negative();
+        let kind = if value.is_nan() {
+            Kind::Nan
+        } else if value.is_infinite() {
+            Kind::Infinite
+        } else if value == 0.0 {
+            Kind::Zero
+        } else {
+            let bits = value.to_bits();
+            let biased = ((bits >> 52) & 0x7ff) as i64;
+            let fraction = bits & ((1 << 52) - 1);
+            let (mantissa, exponent) = match biased {
+                0 => (fraction, -1074),
+                _ => (fraction | 1 << 52, biased - 1075),
+            };
+            normalize(mantissa, exponent)
+        };
+        Self { negative, kind }
+    }
+}
+
+impl From<u64> for Float {
+    fn from(value: u64) -> Self {
+        let kind = match value {
+            0 => Kind::Zero,
+            _ => normalize(value, 0),
+        };
+        Self {
+            negative: false,
+            kind,
+        }
+    }
+}
+
+impl PartialEq for Float {
+    fn eq(&self, other: &Self) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for Float {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        /// Order of the magnitudes, where both zeros are the same.
+        fn magnitude(kind: Kind) -> Option<(u8, i64, u64)> {
+            match kind {
+                Kind::Zero => Some((0, 0, 0)),
+                Kind::Finite { mantissa, exponent } => {
+                    // Subnormal numbers have the lowest exponent and a smaller mantissa, so
+                    // comparing the highest bits first works for them as well.
+                    let top = exponent + 63 - i64::from(mantissa.leading_zeros());
+                    Some((1, top, mantissa << mantissa.leading_zeros()))
+                }
+                Kind::Infinite => Some((2, 0, 0)),
+                Kind::Nan => None,
+            }
+        }
+
+        let (a, b) = (magnitude(self.kind)?, magnitude(other.kind)?);
+        let sign = |negative: bool, magnitude: (u8, i64, u64)| match magnitude.0 {
+            0 => false,
+            _ => negative,
+        };
+        let (a_negative, b_negative) = (sign(self.negative, a), sign(other.negative, b));
+
+        Some(match (a_negative, b_negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => a.cmp(&b),
+            (true, true) => b.cmp(&a),
+        })
+    }
+}
+
+/// Prints a number like `printf` does. With an explicit precision it's printed like the `%Lf`
+/// conversion, otherwise like `%Lg`, which is used when the precision can't be inferred from the
+/// operands.
+///
+/// In the latter case, the number is rounded to 6 significant digits and printed in fixed point
+/// notation if its exponent is within `-4..6`, otherwise in scientific notation. Trailing zeros are
+/// removed in both cases.
+impl fmt::Display for Float {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let positive = !self.negative;
+        if !self.is_finite() {
+            return f.pad_integral(positive, "", &self.to_fixed(0));
+        }
+
+        if let Some(precision) = f.precision() {
+            return f.pad_integral(positive, "", &self.to_fixed(precision));
+        }
+
+        let (mantissa, exponent) = self.significant(Self::PRECISION);
+        let digits = if (-4..Self::PRECISION).contains(&exponent) {
+            let fixed = self.to_fixed((Self::PRECISION - 1 - exponent) as usize);
+            trim_fraction(&fixed).to_owned()
+        } else {
+            let mantissa = format!("{}.{}", &mantissa[..1], &mantissa[1..]);
+            format!(
+                "{}e{}{:02}",
+                trim_fraction(&mantissa),
+                if exponent < 0 { '-' } else { '+' },
+                exponent.abs()
+            )
+        };
+
+        f.pad_integral(positive, "", &digits)
+    }
+}
+
+/// Remove trailing zeros from the fractional part, including the decimal point if nothing is left.
+fn trim_fraction(s: &str) -> &str {
+    if s.contains('.') {
+        s.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        s
+    }
+}
+
+const NAN: Float = Float {
+    negative: false,
+    kind: Kind::Nan,
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn float(arg: &str) -> Float {
+        parse(arg).unwrap()
+    }
+
+    #[test]
+    fn parse_all_forms() {
+        assert_eq!(Some(Float::from(16.0)), parse("0x10"));
+        assert_eq!(Some(Float::from(-0.25)), parse("-0x1p-2"));
+        assert_eq!(Some(Float::from(1.5)), parse("0X1.8"));
+        assert_eq!(Some(Float::from(1000.0)), parse("1e3"));
+        assert_eq!(Some(Float::from(5.0)), parse("+5"));
+        assert_eq!(Some(Float::from(f64::NEG_INFINITY)), parse("-inf"));
+        assert!(parse("NaN").unwrap().is_nan());
+        for arg in &["abc", "0x", "0x1g", "1e", ".", "1e+", "--1", "1.5.2"] {
+            assert!(parse(arg).is_none(), "{}", arg);
+        }
+    }
+
+    #[test]
+    fn parse_rounds_like_strtold() {
+        // Compared with `printf("%.0Lf", strtold(...))` on x86.
+        assert_eq!(
+            "999999999999999999993126004485993267200",
+            float("1e39").to_fixed(0)
+        );
+        assert_eq!("1000000000000000000024696061952", float("1e30").to_fixed(0));
+        assert_eq!(
+            "0.1000000000000000000013552527156068805425",
+            float("0.1").to_fixed(40)
+        );
+        assert_eq!(
+            "18446744073709551615",
+            float("18446744073709551615").to_fixed(0)
+        );
+        // Halfway between two numbers, which goes to the even one.
+        assert_eq!(
+            "18446744073709551616",
+            float("18446744073709551617").to_fixed(0)
+        );
+        assert_eq!(
+            "18446744073709551620",
+            float("18446744073709551619").to_fixed(0)
+        );
+        assert!(float("1e4933").is_infinite());
+        assert!(float("1e-4952").is_zero());
+        assert!(!float("4e-4951").is_zero());
+        assert!(float("0x1p16384").is_infinite());
+    }
+
+    #[test]
+    fn arithmetic_rounds_every_step() {
+        let (first, step) = (float("1e39"), float("1e39"));
+        let third = first.add(Float::from(2).mul(step));
+        assert_eq!(
+            "3000000000000000000053164989752818008064",
+            third.to_fixed(0)
+        );
+        assert_eq!(Float::from(0.0), float("0.1").add(float("-0.1")));
+        assert!(!float("0.1").add(float("-0.1")).is_sign_negative());
+        assert!(float("1").add(float("1e-30")) == float("1"));
+        assert!(float("1").add(float("-1e-30")) == float("1"));
+        assert!(float("1").add(float("-1e-19")) < float("1"));
+        assert!(float("inf").add(float("-inf")).is_nan());
+    }
+
+    #[test]
+    fn ordering() {
+        assert!(float("-0") == float("0"));
+        assert!(float("-1") < float("-0.5"));
+        assert!(float("1e-4940") < float("1e-4939"));
+        assert!(float("inf") > float("1e4000"));
+        assert_eq!(None, float("nan").partial_cmp(&float("1")));
+    }
+
+    #[test]
+    fn printf_format() {
+        assert_eq!("16", Float::from(16.0).to_string());
+        assert_eq!("0.25", Float::from(0.25).to_string());
+        assert_eq!("1e+06", Float::from(1e6).to_string());
+        assert_eq!("123457", Float::from(123_456.7).to_string());
+        assert_eq!("1.5e-05", Float::from(0.000_015).to_string());
+        assert_eq!("9.99999e+38", float("999999e33").to_string());
+        assert_eq!("1e+39", float("1e39").to_string());
+        assert_eq!("-inf", Float::from(f64::NEG_INFINITY).to_string());
+        assert_eq!("-002.5", format!("{:06}", Float::from(-2.5)));
+        assert_eq!("-02.50", format!("{:06.2}", Float::from(-2.5)));
+        assert_eq!("0.12", format!("{:.2}", Float::from(0.125)));
+    }
+}
@@ -0,0 +1,298 @@
+//! Sequences of non-negative integers of arbitrary size. Each number is kept as a string of ASCII
+//! digits that is incremented in place, the same way GNU `seq` handles large integers.
+
+use std::cmp::Ordering;
+use std::io::{self, Write};
+
+/// Check whether the argument consists of decimal digits only, which means the sequence can be
+/// calculated with [`write_sequence`].
+pub fn is_integer(arg: &str) -> bool {
+    !arg.is_empty() && arg.bytes().all(|c| c.is_ascii_digit())
+}
+
+/// Size of the blocks in which the output is written. The numbers are collected into a buffer of
+/// this size first, to avoid the overhead of writing each number separately.
+const BLOCK_SIZE: usize = 128 * 1024;
+
+/// Write the sequence from `first` to `last` in steps of `increment` to the output, with each
+/// number separated by `separator`. All arguments must pass the [`is_integer`] check. Returns
+/// whether any number was written.
+pub fn write_sequence(
+    out: &mut impl Write,
+    first: &str,
+    increment: &str,
+    last: &str,
+    separator: &[u8],
+) -> io::Result<bool> {
+    let mut current = Counter::new(first);
+    let increment = trim_zeros(increment.as_bytes());
+    let last = trim_zeros(last.as_bytes());
+
+    if compare(current.as_bytes(), last) == Ordering::Greater {
+        return Ok(false);
+    }
+
+    let mut block = Block::new(out);
+
+    if increment == b"1" {
+        write_consecutive(&mut block, current, last, separator)?;
+    } else {
+        loop {
+            block.push(current.as_bytes())?;
+            block.push(separator)?;
+            current.add(increment);
+
+            if compare(current.as_bytes(), last) == Ordering::Greater {
+                break;
+            }
+        }
+    }
+
+    block.finish(separator.len())?;
+    Ok(true)
+}
+
+/// Write a sequence with an increment of one. Instead of counting up each number separately, the
+/// numbers that only differ in their last few digits are prepared at once, so most numbers are
+/// written by copying a slice of them.
+fn write_consecutive(
+    block: &mut Block<'_, impl Write>,
+    mut current: Counter,
+    last: &[u8],
+    separator: &[u8],
+) -> io::Result<()> {
+    let mut lines = Lines::new(current.as_bytes(), separator);
+
+    loop {
+        let split = current.digits.len() - lines.suffix_len;
+        let (prefix, suffix) = current.digits.split_at(split);
+        let end = if current.digits.len() == last.len() && prefix == &last[..split] {
+            to_index(&last[split..])
+        } else {
+            lines.count() - 1
+        };
+
+        block.push(lines.range(to_index(suffix), end))?;
+
+        if end != lines.count() - 1 {
+            break;
+        }
+
+        let len = current.digits.len();
+        current.digits[split..].fill(b'9');
+        current.add(b"1");
+
+        if compare(current.as_bytes(), last) == Ordering::Greater {
+            break;
+        }
+
+        if current.digits.len() == len {
+            let zeros = current
+                .digits
+                .iter()
+                .rev()
+                .take_while(|&&d| d == b'0')
+                .count();
+            lines.update(current.as_bytes(), zeros + 1 - lines.suffix_len);
+        } else {
+            lines = Lines::new(current.as_bytes(), separator);
+        }
+    }
+
+    Ok(())
+}
+
+/// Output buffer that collects numbers and writes them out in blocks of [`BLOCK_SIZE`].
+struct Block<'a, W> {
+    out: &'a mut W,
+    buf: Vec<u8>,
+}
+
+impl<'a, W: Write> Block<'a, W> {
+    fn new(out: &'a mut W) -> Self {
+        Self {
+            out,
+            buf: Vec::with_capacity(BLOCK_SIZE * 2),
+        }
+    }
+
+    /// Append the bytes to the buffer. A full buffer is written out before, so the bytes of the
+    /// last call are always still present in the buffer.
+    fn push(&mut self, bytes: &[u8]) -> io::Result<()> {
+        if self.buf.len() >= BLOCK_SIZE {
+            self.out.write_all(&self.buf)?;
+            self.buf.clear();
+        }
+
+        self.buf.extend_from_slice(bytes);
+        Ok(())
+    }
+
+    /// Write out the remaining buffer, without the trailing separator of the last number.
+    fn finish(mut self, separator_len: usize) -> io::Result<()> {
+        let len = self.buf.len() - separator_len;
+        self.buf.truncate(len);
+        self.out.write_all(&self.buf)
+    }
+}
+
+/// Amount of trailing digits that are covered by [`Lines`].
+const SUFFIX_LEN: usize = 3;
+
+/// All numbers that share the same digits except for the last [`SUFFIX_LEN`] ones, each followed
+/// by the separator. Shorter numbers use fewer suffix digits, so the prefix is never empty.
+struct Lines {
+    bytes: Vec<u8>,
+    line_len: usize,
+    suffix_len: usize,
+}
+
+impl Lines {
+    /// Create the lines for the given number, the value of its suffix digits is ignored.
+    fn new(digits: &[u8], separator: &[u8]) -> Self {
+        let suffix_len = SUFFIX_LEN.min(digits.len() - 1);
+        let prefix = &digits[..digits.len() - suffix_len];
+        let mut suffix = vec![b'0'; suffix_len];
+        let mut bytes = Vec::new();
+
+        for i in 0..10_usize.pow(suffix_len as u32) {
+            let mut value = i;
+            for digit in suffix.iter_mut().rev() {
+                *digit = b'0' + (value % 10) as u8;
+                value /= 10;
+            }
+
+            bytes.extend_from_slice(prefix);
+            bytes.extend_from_slice(&suffix);
+            bytes.extend_from_slice(separator);
+        }
+
+        Self {
+            bytes,
+            line_len: digits.len() + separator.len(),
+            suffix_len,
+        }
+    }
+
+    /// Amount of lines, one for each possible suffix.
+    fn count(&self) -> usize {
+        self.bytes.len() / self.line_len
+    }
+
+    /// Update the lines to a new number of the same length, of which only the last `changed`
+    /// digits of the prefix differ from the current one.
+    fn update(&mut self, digits: &[u8], changed: usize) {
+        let end = digits.len() - self.suffix_len;
+        let start = end - changed;
+
+        for line in self.bytes.chunks_exact_mut(self.line_len) {
+            line[start..end].copy_from_slice(&digits[start..end]);
+        }
+    }
+
+    /// Get the lines from the suffix at index `from` up to the one at `to`, both inclusive.
+    fn range(&self, from: usize, to: usize) -> &[u8] {
+        &self.bytes[from * self.line_len..(to + 1) * self.line_len]
+    }
+}
+
+/// Convert a few ASCII digits into the index of the corresponding line in [`Lines`].
+fn to_index(digits: &[u8]) -> usize {
+    digits
+        .iter()
+        .fold(0, |index, &d| index * 10 + usize::from(d - b'0'))
+}
+
+/// An integer of arbitrary size, stored as ASCII digits with the most significant digit first.
+struct Counter {
+    digits: Vec<u8>,
+}
+
+impl Counter {
+    fn new(digits: &str) -> Self {
+        Self {
+            digits: trim_zeros(digits.as_bytes()).to_vec(),
+        }
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        &self.digits
+    }
+
+    /// Add a number in the same ASCII representation, carrying over into new digits as needed.
+    fn add(&mut self, value: &[u8]) {
+        if value.len() > self.digits.len() {
+            let missing = value.len() - self.digits.len();
+            self.digits.splice(0..0, vec![b'0'; missing]);
+        }
+
+        let mut carry = 0;
+        let mut value = value.iter().rev();
+
+        for digit in self.digits.iter_mut().rev() {
+            let sum = match value.next() {
+                Some(v) => *digit - b'0' + v - b'0' + carry,
+                None if carry == 0 => break,
+                None => *digit - b'0' + carry,
+            };
+
+            *digit = b'0' + sum % 10;
+            carry = sum / 10;
+        }
+
+        if carry > 0 {
+            self.digits.insert(0, b'1');
+        }
+    }
+}
+
+/// Remove leading zeros, but keep a single `0` if the number is zero.
+fn trim_zeros(digits: &[u8]) -> &[u8] {
+    let start = digits
+        .iter()
+        .position(|&c| c != b'0')
+        .unwrap_or_else(|| digits.len().saturating_sub(1));
+    &digits[start..]
+}
+
+/// Compare two numbers without leading zeros by their amount of digits first, and only look at
+/// the digits themselves if both are of the same length.
+fn compare(a: &[u8], b: &[u8]) -> Ordering {
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sequence(first: &str, increment: &str, last: &str) -> String {
+        let mut out = Vec::new();
+        write_sequence(&mut out, first, increment, last, b" ").unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn carries_into_new_digits() {
+        assert_eq!("98 99 100 101", sequence("98", "1", "101"));
+        assert_eq!("5 999 1993", sequence("5", "994", "2000"));
+        assert_eq!(
+            "18446744073709551615 18446744073709551616 18446744073709551617",
+            sequence("18446744073709551615", "1", "18446744073709551617")
+        );
+    }
+
+    #[test]
+    fn ignores_leading_zeros() {
+        assert_eq!("0 2 4", sequence("000", "02", "0004"));
+        assert_eq!("", sequence("010", "1", "9"));
+    }
+
+    #[test]
+    fn consecutive_numbers_across_runs() {
+        let expected = (7..=1234).map(|n| n.to_string()).collect::<Vec<_>>();
+        assert_eq!(expected.join(" "), sequence("7", "1", "1234"));
+        assert_eq!("0", sequence("0", "1", "0"));
+        assert_eq!("9 10", sequence("9", "1", "10"));
+        assert_eq!("99999 100000 100001", sequence("99999", "1", "100001"));
+    }
+}
@@ -5,27 +5,52 @@
 //!
 //! See <https://linux.die.net/man/1/seq> for further information about the original `seq` program.
 
-use std::fmt::Write as FmtWrite;
-use std::io::Write as IoWrite;
+use std::fmt::Display;
+use std::io::{self, BufWriter, Write as IoWrite};
 use std::iter::Peekable;
 
-use anyhow::{bail, Context, Result};
+use anyhow::{anyhow, Result};
+
+use crate::decimal::Decimal;
+use crate::float::Float;
+
+mod bignum;
+mod decimal;
+mod float;
+mod integer;
 
 /// Structure to hold all supported options of the application.
 struct Options {
-    separator: String,
+    separator: Vec<u8>,
+    terminator: Vec<u8>,
+    equal_width: bool,
 }
 
 impl Options {
     /// Create a new instance with default values.
     fn new() -> Self {
         Self {
-            separator: "\n".to_owned(),
+            separator: b"\n".to_vec(),
+            terminator: b"\n".to_vec(),
+            equal_width: false,
         }
     }
 }
 
-fn main() -> Result<()> {
+fn main() {
+    if let Err(e) = run() {
+        // Stop quietly if the reader went away, for example when the output is piped into `head`.
+        if matches!(e.downcast_ref::<io::Error>(), Some(e) if e.kind() == io::ErrorKind::BrokenPipe)
+        {
+            return;
+        }
+
+        eprintln!("{}: {}", env!("CARGO_BIN_NAME"), e);
+        std::process::exit(1);
+    }
+}
+
+fn run() -> Result<()> {
     let mut args = std::env::args().skip(1).peekable();
 
     if args.peek().is_none() {
@@ -38,28 +63,314 @@ fn main() -> Result<()> {
         None => return Ok(()),
     };
 
-    let (first, increment, last) = parse_arguments(&mut args)?;
+    let (first, increment, last) = parse_arguments(args)?;
+    let layout = Layout::new(&first, &increment, &last, options.equal_width);
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    let mut written = false;
+    // Decimals and integers have no negative zero, so a first number of `-0` is printed as a float.
+    let negative_zero = first.value.is_zero() && first.value.is_sign_negative();
+
+    if !options.equal_width
+        && !negative_zero
+        && [&first, &increment, &last]
+            .iter()
+            .all(|operand| integer::is_integer(&operand.text))
+    {
+        written = integer::write_sequence(
+            &mut out,
+            &first.text,
+            &increment.text,
+            &last.text,
+            &options.separator,
+        )?;
+    } else {
+        // Exact decimals give the same numbers as the `long double` arithmetic of GNU `seq`, as
+        // long as they are small enough. Larger numbers continue in extended precision, so they
+        // come with the same rounding errors.
+        let mut start = 0_u64;
+        let mut finished = false;
+
+        if let (Some(exact_first), Some(exact_increment), Some(exact_last)) =
+            (first.exact, increment.exact, exact_limit(&last))
+        {
+            let descending = exact_increment.is_negative();
+
+            while let Some(current) = exact_increment
+                .checked_mul(start.into())
+                .and_then(|step| exact_first.checked_add(step))
+                .filter(|current| current.fits_long_double())
+            {
+                if is_past(current, exact_last, descending) {
+                    finished = true;
+                    break;
+                }
+
+                if start == 0 && negative_zero {
+                    write_number(&mut out, &options, true, first.value, layout)?;
+                } else {
+                    write_number(&mut out, &options, start == 0, current, layout)?;
+                }
+                written = true;
+                start += 1;
+            }
+        }
+
+        if !finished {
+            let descending = increment.value.is_sign_negative();
+            let mut previous = None;
 
-    let mut current = first;
-    let mut buf = String::new();
+            for i in start.. {
+                // Multiplying zero with an infinite increment would result in NaN, so the first
+                // number is taken as is.
+                let current = match i {
+                    0 => first.value,
+                    _ => first.value.add(Float::from(i).mul(increment.value)),
+                };
+                // Once the increment is lost to rounding, like in `seq 1e30 1 inf`, the sequence
+                // would repeat the same number practically forever.
+                if is_past(current, last.value, descending) || previous == Some(current) {
+                    break;
+                }
+                previous = Some(current);
 
-    while current <= last {
-        if !buf.is_empty() {
-            buf.write_str(&options.separator).unwrap();
+                write_number(&mut out, &options, i == 0, current, layout)?;
+                written = true;
+            }
         }
+    }
 
-        write!(buf, "{}", current).unwrap();
-        current += increment;
+    if written {
+        out.write_all(&options.terminator)?;
     }
 
-    std::io::stdout()
-        .write_all(&buf.as_bytes())
-        .map_err(Into::into)
+    out.flush().map_err(Into::into)
+}
+
+/// Get the exact value of the last number. An infinite value is replaced with the largest or
+/// smallest representable number, as the sequence can't count beyond those anyway.
+fn exact_limit(last: &Operand) -> Option<Decimal> {
+    if last.value.is_infinite() && !last.value.is_sign_negative() {
+        Some(Decimal::MAX)
+    } else if last.value.is_infinite() {
+        Some(Decimal::MIN)
+    } else {
+        last.exact
+    }
+}
+
+/// Check whether the sequence went past the last number, which depends on the direction it's
+/// counting in.
+fn is_past<T: PartialOrd>(current: T, last: T, descending: bool) -> bool {
+    if descending {
+        current < last
+    } else {
+        current > last
+    }
+}
+
+/// A number that can be printed in fixed point notation with any precision. The formatting
+/// machinery of the standard library can't do that, as it panics for a width or precision above
+/// 65535, which operands like `1e-70000` lead to.
+trait FixedPoint: Display {
+    fn is_sign_negative(&self) -> bool;
+    /// Print the absolute value with `precision` fractional digits.
+    fn to_fixed(&self, precision: usize) -> String;
+}
+
+impl FixedPoint for Decimal {
+    fn is_sign_negative(&self) -> bool {
+        self.is_negative()
+    }
+
+    fn to_fixed(&self, precision: usize) -> String {
+        Decimal::to_fixed(*self, precision)
+    }
+}
+
+impl FixedPoint for Float {
+    fn is_sign_negative(&self) -> bool {
+        Float::is_sign_negative(self)
+    }
+
+    fn to_fixed(&self, precision: usize) -> String {
+        Float::to_fixed(*self, precision)
+    }
+}
+
+/// Write a single number to the output, preceded by the separator if it's not the first one.
+fn write_number(
+    out: &mut impl IoWrite,
+    options: &Options,
+    first: bool,
+    number: impl FixedPoint,
+    layout: Option<Layout>,
+) -> io::Result<()> {
+    if !first {
+        out.write_all(&options.separator)?;
+    }
+
+    match layout {
+        Some(Layout { width, precision }) => {
+            // Zero padding goes after the sign.
+            let sign = if number.is_sign_negative() { "-" } else { "" };
+            let digits = number.to_fixed(precision);
+            let zeros = width.saturating_sub(sign.len() + digits.len());
+            write!(out, "{}{}{}", sign, "0".repeat(zeros), digits)
+        }
+        None => write!(out, "{}", number),
+    }
+}
+
+/// A positional argument, together with the layout information that is derived from the way it
+/// was written on the command line.
+struct Operand {
+    /// The argument as it was written on the command line.
+    text: String,
+    value: Float,
+    /// The same value as exact decimal number, if it could be represented as such.
+    exact: Option<Decimal>,
+    /// Amount of digits after the decimal point, or `None` if it can't be inferred from the input.
+    precision: Option<usize>,
+    /// Amount of characters needed to print the value with its precision.
+    width: usize,
+}
+
+impl Operand {
+    /// Parse a single argument and infer its precision and width the same way GNU `seq` does. A
+    /// leading `+` is not counted towards the width as it's never printed. Hexadecimal integers
+    /// have no precision and don't count towards the width, while the precision of other
+    /// hexadecimal and infinite numbers can't be inferred.
+    fn parse(arg: &str) -> Result<Self> {
+        let text = arg.to_owned();
+        let value = float::parse(arg).ok_or_else(|| {
+            usage_error(format_args!("invalid floating point argument: '{}'", arg))
+        })?;
+        let arg = arg.trim_start_matches('+');
+        let mut width = arg.len() as isize;
+        let mut precision = None;
+
+        if value.is_finite() && !arg.contains(['x', 'X']) {
+            let decimal_point = arg.find('.');
+            let exponent_start = arg.find(['e', 'E']);
+            let mut prec = 0;
+
+            if let Some(pos) = decimal_point {
+                prec = exponent_start.unwrap_or(arg.len()) - pos - 1;
+                if prec == 0 {
+                    width -= 1;
+                } else if pos == 0 || !arg.as_bytes()[pos - 1].is_ascii_digit() {
+                    width += 1;
+                }
+            }
+
+            if let Some(pos) = exponent_start {
+                let exponent: isize = arg[pos + 1..].parse().unwrap_or_default();
+                width -= (arg.len() - pos) as isize;
+
+                if exponent < 0 {
+                    if decimal_point.is_none_or(|dp| pos == dp + 1) {
+                        width += 1;
+                    }
+                    prec += exponent.unsigned_abs();
+                    width += -exponent;
+                } else {
+                    // Fractional digits are moved in front of the decimal point, further digits
+                    // are filled up with zeros.
+                    let shifted = prec.min(exponent as usize);
+                    prec -= shifted;
+                    if decimal_point.is_some() && prec == 0 && width > 0 {
+                        width -= 1;
+                    }
+                    width += exponent - shifted as isize;
+                }
+            }
+
+            precision = Some(prec);
+        } else if value.is_finite() && !arg.contains(['.', 'p', 'P']) {
+            width = 0;
+            precision = Some(0);
+        }
+
+        Ok(Self {
+            text,
+            value,
+            exact: Decimal::parse(arg),
+            precision,
+            width: width.max(0) as usize,
+        })
+    }
+}
+
+/// Output layout of the numbers, which is derived from the operands as GNU `seq` does.
+#[derive(Clone, Copy)]
+struct Layout {
+    /// Minimum width, filled up with leading zeros. Only set for the `-w / --equal-width` option.
+    width: usize,
+    /// Amount of digits after the decimal point.
+    precision: usize,
+}
+
+impl Layout {
+    /// Calculate the common layout for the sequence. Every number is printed with the precision of
+    /// `first` and `increment`, whichever is higher. If `equal_width` is set, the width is taken
+    /// from `first` and `last`, adjusted to that precision, or only from `first` if `last` is
+    /// infinite. Returns `None` if the precision of any operand is unknown, except for an infinite
+    /// `last`.
+    fn new(
+        first: &Operand,
+        increment: &Operand,
+        last: &Operand,
+        equal_width: bool,
+    ) -> Option<Self> {
+        if last.precision.is_none() && last.value.is_finite() {
+            return None;
+        }
+
+        let first_precision = first.precision?;
+        let precision = first_precision.max(increment.precision?);
+
+        if !equal_width {
+            return Some(Self {
+                width: 0,
+                precision,
+            });
+        }
+
+        let mut first_width = first.width + (precision - first_precision);
+        if first_precision == 0 && precision > 0 {
+            first_width += 1;
+        }
+
+        let last_width = match last.precision {
+            Some(last_precision) => {
+                let mut last_width = (last.width + precision).saturating_sub(last_precision);
+                if last_precision > 0 && precision == 0 {
+                    last_width = last_width.saturating_sub(1);
+                }
+                if last_precision == 0 && precision > 0 {
+                    last_width += 1;
+                }
+                last_width
+            }
+            None => 0,
+        };
+
+        Some(Self {
+            width: first_width.max(last_width),
+            precision,
+        })
+    }
 }
 
 /// Check wheather an argument is identified as option, that is it starts with a dash (`-`) or
-/// double dash (`--`) and the following character is a letter.
+/// double dash (`--`) and the following character is a letter. Negative numbers like `-inf` are
+/// not considered an option.
 fn is_option(arg: Option<&String>) -> bool {
+    if arg.is_some_and(|arg| arg.parse::<f64>().is_ok()) {
+        return false;
+    }
+
     let mut chars = arg.map(|v| v.as_str()).unwrap_or_default().chars();
 
     match (chars.next(), chars.next(), chars.next()) {
@@ -83,6 +394,8 @@ Print numbers from FIRST to LAST, in steps of INCREMENT.
 
 Mandatory arguments to long options are mandatory for short options too.
   -s, --separator STRING   use STRING to separate numbers (default: \\n)
+  -t, --terminator STRING  use STRING to terminate the sequence (default: \\n)
+  -w, --equal-width        equalize width by padding with leading zeroes
   -h, --help               display this help and exit
   -V, --version            output version information and exit
 
@@ -110,6 +423,8 @@ fn print_version() {
 ///
 /// Possible options are:
 /// - `-s / --separator` to pick another separator than `\n`.
+/// - `-t / --terminator` to pick another terminator than `\n`.
+/// - `-w / --equal-width` to pad all numbers with leading zeros to the same width.
 /// - `-h / --help` to show the help message.
 /// - `-V / --version` to show version information.
 fn parse_options(args: &mut Peekable<impl Iterator<Item = String>>) -> Result<Option<Options>> {
@@ -120,8 +435,16 @@ fn parse_options(args: &mut Peekable<impl Iterator<Item = String>>) -> Result<Op
             "-s" | "--separator" => {
                 options.separator = args
                     .next()
-                    .context("expected STRING value for separator option")?
+                    .map(|value| unescape(&value))
+                    .ok_or_else(|| usage_error("expected STRING value for separator option"))?
             }
+            "-t" | "--terminator" => {
+                options.terminator = args
+                    .next()
+                    .map(|value| unescape(&value))
+                    .ok_or_else(|| usage_error("expected STRING value for terminator option"))?
+            }
+            "-w" | "--equal-width" => options.equal_width = true,
             "-h" | "--help" => {
                 print_help();
                 return Ok(None);
@@ -130,26 +453,176 @@ fn parse_options(args: &mut Peekable<impl Iterator<Item = String>>) -> Result<Op
                 print_version();
                 return Ok(None);
             }
-            s => bail!("unknown option '{}'", s),
+            s => return Err(usage_error(format_args!("unknown option '{}'", s))),
         }
     }
 
     Ok(Some(options))
 }
 
+/// Interpret backslash escapes in the value of the separator and terminator options, which are the
+/// same as supported by `printf`. Unknown escapes are kept as they are.
+fn unescape(value: &str) -> Vec<u8> {
+    let bytes = value.as_bytes();
+    let mut result = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let (byte, len) = match (bytes[i], bytes.get(i + 1)) {
+            (b'\\', Some(b'\\')) => (b'\\', 2),
+            (b'\\', Some(b'a')) => (0x07, 2),
+            (b'\\', Some(b'b')) => (0x08, 2),
+            (b'\\', Some(b'e')) => (0x1b, 2),
+            (b'\\', Some(b'f')) => (0x0c, 2),
+            (b'\\', Some(b'n')) => (b'\n', 2),
+            (b'\\', Some(b'r')) => (b'\r', 2),
+            (b'\\', Some(b't')) => (b'\t', 2),
+            (b'\\', Some(b'v')) => (0x0b, 2),
+            (b'\\', Some(b'0')) => {
+                let (byte, digits) = parse_escaped_number(&bytes[i + 2..], 8, 3);
+                (byte, 2 + digits)
+            }
+            (b'\\', Some(b'x')) => match parse_escaped_number(&bytes[i + 2..], 16, 2) {
+                (_, 0) => (b'\\', 1),
+                (byte, digits) => (byte, 2 + digits),
+            },
+            (byte, _) => (byte, 1),
+        };
+
+        result.push(byte);
+        i += len;
+    }
+
+    result
+}
+
+/// Parse up to `max_digits` digits of a numeric escape sequence in the given radix. Returns the
+/// resulting byte, with any overflow discarded, and the amount of digits that were used.
+fn parse_escaped_number(bytes: &[u8], radix: u32, max_digits: usize) -> (u8, usize) {
+    bytes
+        .iter()
+        .take(max_digits)
+        .map_while(|&b| char::from(b).to_digit(radix))
+        .fold((0_u8, 0), |(value, digits), digit| {
+            (
+                value.wrapping_mul(radix as u8).wrapping_add(digit as u8),
+                digits + 1,
+            )
+        })
+}
+
 /// Parse all possible combinations of positional arguments.
 ///
 /// The possible combinations are:
 /// - `LAST` to count from 1 to LAST with increments of 1.
 /// - `FIRST LAST` to count from FIRST to LAST with increments of 1.
 /// - `FIRST INCREMENT LAST` to count from FIRST to LAST with increments of INCREMENT.
-fn parse_arguments(args: &mut impl Iterator<Item = String>) -> Result<(f64, f64, f64)> {
-    Ok(match (args.next(), args.next(), args.next()) {
-        (Some(last), None, None) => (1.0, 1.0, last.parse()?),
-        (Some(first), Some(last), None) => (first.parse()?, 1.0, last.parse()?),
-        (Some(first), Some(increment), Some(last)) => {
-            (first.parse()?, increment.parse()?, last.parse()?)
-        }
-        _ => bail!("too many arguments"),
-    })
+fn parse_arguments(args: impl Iterator<Item = String>) -> Result<(Operand, Operand, Operand)> {
+    let operands = args.collect::<Vec<_>>();
+    let (first, increment, last) = match operands.as_slice() {
+        [] => return Err(usage_error("missing operand")),
+        [last] => (
+            Operand::parse("1")?,
+            Operand::parse("1")?,
+            Operand::parse(last)?,
+        ),
+        [first, last] => (
+            Operand::parse(first)?,
+            Operand::parse("1")?,
+            Operand::parse(last)?,
+        ),
+        [first, increment, last] => (
+            Operand::parse(first)?,
+            Operand::parse(increment)?,
+            Operand::parse(last)?,
+        ),
+        [_, _, _, extra, ..] => return Err(usage_error(format_args!("extra operand '{}'", extra))),
+    };
+
+    if let Some(operand) = [&first, &increment, &last]
+        .iter()
+        .find(|operand| operand.value.is_nan())
+    {
+        return Err(usage_error(format_args!(
+            "invalid 'not-a-number' argument: '{}'",
+            operand.text
+        )));
+    }
+
+    if increment.value.is_zero() {
+        return Err(usage_error(format_args!(
+            "invalid Zero increment value: '{}'",
+            increment.text
+        )));
+    }
+
+    Ok((first, increment, last))
+}
+
+/// Create an error for invalid usage of the command line interface, that points the user to the
+/// help message for further information.
+fn usage_error(msg: impl Display) -> anyhow::Error {
+    anyhow!(
+        "{}\nTry '{} --help' for more information.",
+        msg,
+        env!("CARGO_BIN_NAME")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unescape_sequences() {
+        assert_eq!(b"\t\n\\".to_vec(), unescape(r"\t\n\\"));
+        assert_eq!(b"\0A|A".to_vec(), unescape(r"\0\0101|\x41"));
+        assert_eq!(br"\q\x".to_vec(), unescape(r"\q\x"));
+        assert_eq!(br"end\".to_vec(), unescape(r"end\"));
+    }
+
+    #[test]
+    fn equal_width_without_precision_of_last() {
+        let layout = |first, increment, last| {
+            let operand = |arg| Operand::parse(arg).unwrap();
+            Layout::new(&operand(first), &operand(increment), &operand(last), true)
+                .map(|layout| (layout.width, layout.precision))
+        };
+
+        assert_eq!(Some((4, 1)), layout("1", "0.5", "10"));
+        // Compared with GNU seq -w
+        assert_eq!(Some((3, 1)), layout("1", "0.5", "0x4"));
+        assert_eq!(Some((4, 1)), layout("-1", "0.5", "0x2"));
+        assert_eq!(Some((4, 1)), layout("10", "-0.5", "-inf"));
+        assert_eq!(Some((2, 0)), layout("1", "0x2", "10"));
+        assert_eq!(Some((2, 0)), layout("0x1", "1", "10"));
+        assert_eq!(Some((2, 0)), layout("-0x1", "1", "10"));
+        assert_eq!(Some((3, 1)), layout("0x1", "0.5", "3"));
+        assert_eq!(None, layout("0x1.0", "0.5", "3"));
+        assert_eq!(None, layout("1", "0.5", "0x1.8p1"));
+    }
+
+    #[test]
+    fn precision_beyond_the_formatter() {
+        let layout = Some(Layout {
+            width: 70_004,
+            precision: 70_000,
+        });
+        let write = |number: &dyn Fn(&mut Vec<u8>) -> io::Result<()>| {
+            let mut out = Vec::new();
+            number(&mut out).unwrap();
+            out
+        };
+
+        let options = Options::new();
+        let float = write(&|out| write_number(out, &options, true, Float::from(-0.5), layout));
+        assert_eq!(70_004, float.len());
+        assert!(float.starts_with(b"-00.50"));
+        assert!(float.ends_with(b"000"));
+
+        let decimal = Decimal::parse("1.25").unwrap();
+        let decimal = write(&|out| write_number(out, &options, true, decimal, layout));
+        assert_eq!(70_004, decimal.len());
+        assert!(decimal.starts_with(b"001.250"));
+    }
 }
@@ -0,0 +1,151 @@
+use std::io::{BufRead, BufReader};
+use std::process::{Command, Output, Stdio};
+
+fn seq(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_seq"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+/// Run `seq` and check that it succeeds with the expected output and nothing on stderr.
+fn assert_output(args: &[&str], expected: &str) {
+    let output = seq(args);
+    assert_eq!(Some(0), output.status.code(), "{:?}", args);
+    assert_eq!(
+        expected,
+        String::from_utf8_lossy(&output.stdout),
+        "{:?}",
+        args
+    );
+    assert_eq!("", String::from_utf8_lossy(&output.stderr), "{:?}", args);
+}
+
+/// Run `seq` and check that it fails with the expected message and the hint to `--help`, like
+/// GNU does for invalid operands.
+fn assert_usage_error(args: &[&str], message: &str) {
+    let output = seq(args);
+    assert_eq!(Some(1), output.status.code(), "{:?}", args);
+    assert_eq!("", String::from_utf8_lossy(&output.stdout), "{:?}", args);
+    assert_eq!(
+        format!("seq: {}\nTry 'seq --help' for more information.\n", message),
+        String::from_utf8_lossy(&output.stderr),
+        "{:?}",
+        args
+    );
+}
+
+/// Spawn `seq` with its output in a pipe, read the first lines of it and then close the pipe.
+/// Returns the lines and the finished process.
+fn read_lines(args: &[&str], count: usize) -> (Vec<String>, Output) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_seq"))
+        .args(args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let lines = BufReader::new(child.stdout.take().unwrap())
+        .lines()
+        .take(count)
+        .collect::<Result<_, _>>()
+        .unwrap();
+    (lines, child.wait_with_output().unwrap())
+}
+
+#[test]
+fn descending_sequences() {
+    assert_output(&["10", "-1", "1"], "10\n9\n8\n7\n6\n5\n4\n3\n2\n1\n");
+    assert_output(&["-1", "-0.5", "-3"], "-1.0\n-1.5\n-2.0\n-2.5\n-3.0\n");
+    assert_output(&["3", "-2", "-3"], "3\n1\n-1\n-3\n");
+}
+
+#[test]
+fn exponent_operands() {
+    assert_output(&["1e3", "1e3", "3e3"], "1000\n2000\n3000\n");
+    assert_output(
+        &["1.5e-2", "0.5e-2", "3e-2"],
+        "0.015\n0.020\n0.025\n0.030\n",
+    );
+    // Beyond the range of exact decimals, the numbers carry the rounding errors of `long double`
+    // like in GNU.
+    assert_output(
+        &["1e39", "1e39"],
+        "999999999999999999993126004485993267200\n",
+    );
+    assert_output(
+        &["-w", "1e39", "1e39", "3e39"],
+        "0999999999999999999993126004485993267200\n\
+         1999999999999999999986252008971986534400\n\
+         3000000000000000000053164989752818008064\n",
+    );
+    assert_output(&["1e30", "1e30"], "1000000000000000000024696061952\n");
+}
+
+#[test]
+fn hexadecimal_operands() {
+    assert_output(&["0x10", "0x10", "0x30"], "16\n32\n48\n");
+    // Without a precision to infer, the numbers are printed like `%g` does.
+    assert_output(&["1", "0x1p-2", "2"], "1\n1.25\n1.5\n1.75\n2\n");
+    assert_output(&["1", "0.5", "0x1.8p1"], "1\n1.5\n2\n2.5\n3\n");
+}
+
+#[test]
+fn empty_when_last_is_in_the_other_direction() {
+    assert_output(&["5", "1"], "");
+    assert_output(&["1", "-1", "5"], "");
+    assert_output(&["-s", ",", "5", "1"], "");
+}
+
+#[test]
+fn reject_zero_increment() {
+    assert_usage_error(&["1", "0", "5"], "invalid Zero increment value: '0'");
+    assert_usage_error(&["1", "-0", "5"], "invalid Zero increment value: '-0'");
+    assert_usage_error(&["1", "0.0", "5"], "invalid Zero increment value: '0.0'");
+}
+
+#[test]
+fn reject_not_a_number() {
+    assert_usage_error(&["nan"], "invalid 'not-a-number' argument: 'nan'");
+    assert_usage_error(&["1", "nan"], "invalid 'not-a-number' argument: 'nan'");
+    assert_usage_error(&["1", "NaN", "5"], "invalid 'not-a-number' argument: 'NaN'");
+}
+
+#[test]
+fn reject_extra_operand() {
+    assert_usage_error(&["1", "2", "3", "4"], "extra operand '4'");
+    assert_usage_error(&["1", "2", "3", "4", "5"], "extra operand '4'");
+}
+
+#[test]
+fn infinite_last_number() {
+    let (lines, output) = read_lines(&["inf"], 3);
+    assert_eq!(vec!["1", "2", "3"], lines);
+    assert_eq!(Some(0), output.status.code());
+
+    let (lines, output) = read_lines(&["3", "-1", "-inf"], 3);
+    assert_eq!(vec!["3", "2", "1"], lines);
+    assert_eq!(Some(0), output.status.code());
+
+    let (lines, output) = read_lines(&["-1", "-0.5", "-inf"], 3);
+    assert_eq!(vec!["-1.0", "-1.5", "-2.0"], lines);
+    assert_eq!(Some(0), output.status.code());
+
+    assert_output(&["inf", "1"], "");
+    assert_output(&["0", "-1", "inf"], "");
+}
+
+#[test]
+fn output_starts_before_the_sequence_ends() {
+    // The sequence never ends, so the lines can only be read if they are written while counting.
+    let (lines, _) = read_lines(&["0", "0.5", "inf"], 4);
+    assert_eq!(vec!["0.0", "0.5", "1.0", "1.5"], lines);
+}
+
+#[test]
+fn stop_quietly_when_the_reader_goes_away() {
+    for args in &[&["inf"][..], &["1.5", "inf"], &["-w", "1", "1000000000"]] {
+        let (_, output) = read_lines(args, 1);
+        assert_eq!(Some(0), output.status.code(), "{:?}", args);
+        assert_eq!("", String::from_utf8_lossy(&output.stderr), "{:?}", args);
+    }
+}
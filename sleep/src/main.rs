@@ -4,65 +4,100 @@ use std::process::exit;
 use std::thread::sleep;
 use std::time::Duration;
 
-const DAY_MILLIS: f64 = 86400000.0;
-const HOUR_MILLIS: f64 = 3600000.0;
-const MINUTE_MILLIS: f64 = 60000.0;
-const SECOND_MILLIS: f64 = 1000.0;
+use common::date::Timestamp;
+use common::duration::{self, DurationSpec};
+
+mod progress;
+mod until;
 
 type ParseResult<T> = Result<T, Box<dyn Error>>;
 
-/// parse one token from the user as a duration
-fn parse_time(dur: String) -> ParseResult<f64> {
-    let mut vec_multiplier: Vec<char> = vec![];
-    let mut duration: ParseResult<f64> = Ok(SECOND_MILLIS);
-    // split into float characters (multiplier) and duration (minute, second, hour)
-    for c in dur.chars() {
-        if c.is_numeric() || c == '.' {
-            vec_multiplier.push(c)
+/// what the user asked to sleep for
+enum Request {
+    /// the sum of all given time intervals
+    Interval(Duration),
+    /// a point in time given with `--until`
+    Until(Timestamp),
+}
+
+/// all options given by the user
+struct Args {
+    request: Request,
+    /// show a countdown with `--progress`
+    progress: bool,
+}
+
+/// get all arguments from user
+/// either a point in time is given with `--until TIME`,
+/// or all arguments are parsed as time durations and summed up
+fn parse_args() -> ParseResult<Args> {
+    let mut args = env::args().skip(1);
+    let mut until = None;
+    let mut durations = Vec::new();
+    let mut progress = false;
+
+    while let Some(arg) = args.next() {
+        if arg == "--progress" {
+            progress = true;
+        } else if arg == "--until" {
+            until = Some(args.next().ok_or("option '--until' requires an argument")?);
+        } else if let Some(time) = arg.strip_prefix("--until=") {
+            until = Some(time.to_owned());
         } else {
-            duration = match c {
-                's' => Ok(SECOND_MILLIS),
-                'm' => Ok(MINUTE_MILLIS),
-                'h' => Ok(HOUR_MILLIS),
-                'd' => Ok(DAY_MILLIS),
-                _ => Err(format!("invalid time interval '{}'", dur).into()),
-            };
-            break; // break out of loop once we've found a non numeric character for this token
+            durations.push(DurationSpec::parse(&arg)?);
         }
     }
-    let multiplier: f64 = vec_multiplier
-        .iter()
-        .collect::<String>()
-        .parse()?;
-    match duration {
-        Ok(dur) => Ok(dur * multiplier),
-        Err(e) => Err(e),
+
+    let request = match until {
+        Some(_) if !durations.is_empty() => {
+            return Err("--until can't be combined with a time interval".into())
+        }
+        Some(time) => Request::Until(until::parse_deadline(&time)?),
+        None if durations.is_empty() => return Err("missing operand".into()),
+        None => Request::Interval(duration::total(&durations)),
+    };
+
+    Ok(Args { request, progress })
+}
+
+/// sleep for the whole duration, split into chunks that the platform can handle, so
+/// effectively infinite durations work as well
+fn sleep_for(mut remaining: Duration) {
+    while remaining > Duration::ZERO {
+        let chunk = remaining.min(duration::MAX_WAIT);
+        sleep(chunk);
+        remaining -= chunk;
     }
 }
 
-/// get all arguments from user
-/// if arguments can be parsed as time durations,
-/// return the sum of all parsed durations as milliseconds
-fn parse_args() -> ParseResult<u64> {
-    let durations: Result<Vec<f64>, _> = env::args().skip(1).map(parse_time).collect();
-    match durations {
-        Ok(durs) => {
-            if durs.is_empty() {
-                Err("missing operand".into())
-            } else {
-                Ok(durs.iter().sum::<f64>() as u64)
+/// sleep as requested, showing the countdown first if asked to. the countdown is only shown
+/// on a terminal, otherwise it would just clutter logs and pipes
+fn run(args: Args) -> ParseResult<()> {
+    let progress = args.progress && progress::is_available();
+
+    match args.request {
+        Request::Interval(sleep_duration) => {
+            if !(progress && progress::sleep_with_progress(sleep_duration)) {
+                sleep_for(sleep_duration);
+            }
+            Ok(())
+        }
+        Request::Until(deadline) => {
+            if progress {
+                progress::sleep_with_progress(until::remaining(deadline));
             }
-        },
-        Err(e) => Err(e),
+            // the countdown ends at the right time unless the clock was changed meanwhile,
+            // which sleeping on the realtime clock takes care of
+            until::sleep_until(deadline).map_err(Into::into)
+        }
     }
 }
 
 fn main() {
-    match parse_args() {
-        Ok(sleep_duration_millis) => sleep(Duration::from_millis(sleep_duration_millis)),
-        Err(e) => {
-            eprintln!("sleep error: {}", e);
-            exit(1)
-        }
-    };
+    let result = parse_args().and_then(run);
+
+    if let Err(e) = result {
+        eprintln!("sleep error: {}", e);
+        exit(1)
+    }
 }
@@ -0,0 +1,135 @@
+//! live countdown on the terminal, as shown with the `--progress` option
+
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+/// time between two updates of the countdown
+const TICK: Duration = Duration::from_millis(200);
+/// amount of characters the bar is wide
+const BAR_WIDTH: usize = 30;
+/// moves the cursor to the start of the line and clears it
+const CLEAR_LINE: &str = "\r\x1b[K";
+
+/// set by the signal handler once SIGINT was received
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// check whether the countdown can be shown, which requires stderr to be a terminal
+pub fn is_available() -> bool {
+    // SAFETY: isatty only inspects the file descriptor
+    unsafe { libc::isatty(libc::STDERR_FILENO) == 1 }
+}
+
+/// sleep for the given duration while showing the remaining time and a bar on stderr.
+/// returns `false` without sleeping if the end can't be represented, as no sensible countdown
+/// can be shown for effectively infinite durations
+pub fn sleep_with_progress(total: Duration) -> bool {
+    let start = Instant::now();
+    let end = match start.checked_add(total) {
+        Some(end) => end,
+        None => return false,
+    };
+
+    let previous = install_handler();
+    let mut stderr = io::stderr();
+
+    loop {
+        let now = Instant::now();
+        if now >= end || INTERRUPTED.load(Ordering::SeqCst) {
+            break;
+        }
+
+        let remaining = end - now;
+        // a failed write only means the countdown isn't visible, the sleep goes on anyway
+        let _ = write!(stderr, "{}{}", CLEAR_LINE, render(remaining, total));
+        let _ = stderr.flush();
+        sleep(remaining.min(TICK));
+    }
+
+    let _ = write!(stderr, "{}", CLEAR_LINE);
+    let _ = stderr.flush();
+
+    // SAFETY: the previous handler was returned by signal, so it's valid to restore
+    unsafe { libc::signal(libc::SIGINT, previous) };
+    if INTERRUPTED.load(Ordering::SeqCst) {
+        // die from the signal like any other program would, now that the line is restored
+        // SAFETY: raising a signal has no memory safety requirements
+        unsafe { libc::raise(libc::SIGINT) };
+    }
+
+    true
+}
+
+/// format one line of the countdown, like `0:01:30 [#########.....................]  30%`
+fn render(remaining: Duration, total: Duration) -> String {
+    let done = 1.0 - remaining.as_secs_f64() / total.as_secs_f64();
+    let filled = ((done * BAR_WIDTH as f64) as usize).min(BAR_WIDTH);
+
+    format!(
+        "{} [{}{}] {:3.0}%",
+        format_remaining(remaining),
+        "#".repeat(filled),
+        ".".repeat(BAR_WIDTH - filled),
+        done * 100.0
+    )
+}
+
+/// format the remaining time as `H:MM:SS`, prefixed with the days if there are any. partial
+/// seconds are rounded up, so the countdown shows `0:00:00` only at the very end
+fn format_remaining(remaining: Duration) -> String {
+    let secs = remaining.as_secs() + (remaining.subsec_nanos() > 0) as u64;
+    let (days, secs) = (secs / 86400, secs % 86400);
+    let time = format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60);
+
+    if days > 0 {
+        format!("{}d {}", days, time)
+    } else {
+        time
+    }
+}
+
+extern "C" fn on_interrupt(_: libc::c_int) {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+}
+
+/// catch SIGINT, so the countdown can be cleared before the program ends. an ignored SIGINT,
+/// like in background jobs, stays ignored. returns the previous handler
+fn install_handler() -> libc::sighandler_t {
+    let handler = on_interrupt as extern "C" fn(libc::c_int);
+    // SAFETY: the handler only stores into an atomic, which is async-signal-safe
+    let previous = unsafe { libc::signal(libc::SIGINT, handler as libc::sighandler_t) };
+    if previous == libc::SIG_IGN {
+        // SAFETY: restoring the disposition that was just replaced
+        unsafe { libc::signal(libc::SIGINT, libc::SIG_IGN) };
+    }
+    previous
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_countdown() {
+        assert_eq!("0:00:00", format_remaining(Duration::ZERO));
+        assert_eq!("0:00:01", format_remaining(Duration::from_millis(1)));
+        assert_eq!("1:01:01", format_remaining(Duration::from_secs(3661)));
+        assert_eq!(
+            "2d 0:00:05",
+            format_remaining(Duration::from_secs(2 * 86400 + 5))
+        );
+    }
+
+    #[test]
+    fn render_bar() {
+        assert_eq!(
+            "0:00:30 [###############...............]  50%",
+            render(Duration::from_secs(30), Duration::from_secs(60))
+        );
+        assert_eq!(
+            "0:01:00 [..............................]   0%",
+            render(Duration::from_secs(60), Duration::from_secs(60))
+        );
+    }
+}
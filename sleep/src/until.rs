@@ -0,0 +1,164 @@
+//! sleeping until an absolute point in time, as given with the `--until` option
+
+use std::convert::TryFrom;
+use std::io;
+use std::mem::MaybeUninit;
+use std::ptr;
+use std::time::Duration;
+
+use common::date::{self, Timestamp};
+use common::NANOS_PER_SECOND;
+
+use crate::ParseResult;
+
+/// parse the argument of `--until`, which is a date string like `touch -d` takes, for example
+/// - `@SECONDS` for seconds since the unix epoch, like `@1700000000.5`
+/// - `HH:MM[:SS]` for the next time the local clock shows that time
+/// - `YYYY-MM-DDTHH:MM[:SS[.FRAC]][Z|+HH:MM]` as ISO-8601 timestamp, in local time if no
+///   offset is given
+pub fn parse_deadline(time: &str) -> ParseResult<Timestamp> {
+    let invalid = || format!("invalid time '{}'", time);
+    let now = now().ok_or_else(invalid)?;
+
+    // a time of day alone means today, so it's moved to tomorrow once it has passed. the time
+    // may also be skipped today when daylight saving time starts
+    let deadline = match date::parse(time, now) {
+        Some(deadline) if deadline.secs > now.secs || !date::is_time_of_day(time) => Some(deadline),
+        _ if date::is_time_of_day(time) => date::parse(&format!("{} tomorrow", time), now),
+        _ => None,
+    };
+
+    deadline.ok_or_else(|| invalid().into())
+}
+
+/// sleep until the deadline is reached. this uses the realtime clock, so changes to the system
+/// time are taken into account while sleeping
+pub fn sleep_until(deadline: Timestamp) -> io::Result<()> {
+    let target = libc::timespec {
+        tv_sec: deadline.secs as libc::time_t,
+        tv_nsec: deadline.nanos as libc::c_long,
+    };
+
+    loop {
+        // SAFETY: the timespec is valid and no remaining time is requested for absolute sleeps
+        let result = unsafe {
+            libc::clock_nanosleep(
+                libc::CLOCK_REALTIME,
+                libc::TIMER_ABSTIME,
+                &target,
+                ptr::null_mut(),
+            )
+        };
+
+        match result {
+            0 => return Ok(()),
+            libc::EINTR => continue,
+            errno => return Err(io::Error::from_raw_os_error(errno)),
+        }
+    }
+}
+
+/// time left until the deadline is reached, which is zero once it has passed
+pub fn remaining(deadline: Timestamp) -> Duration {
+    let now = match now() {
+        Some(now) => now,
+        None => return Duration::ZERO,
+    };
+    let nanos = (i128::from(deadline.secs) - i128::from(now.secs)) * i128::from(NANOS_PER_SECOND)
+        + i128::from(deadline.nanos)
+        - i128::from(now.nanos);
+    let per_second = u128::from(NANOS_PER_SECOND);
+
+    match u128::try_from(nanos) {
+        Ok(nanos) => Duration::new(
+            u64::try_from(nanos / per_second).unwrap_or(u64::MAX),
+            (nanos % per_second) as u32,
+        ),
+        Err(_) => Duration::ZERO,
+    }
+}
+
+/// current time of the realtime clock
+fn now() -> Option<Timestamp> {
+    let mut ts = MaybeUninit::uninit();
+    // SAFETY: the pointer is valid for writes and initialized on success
+    if unsafe { libc::clock_gettime(libc::CLOCK_REALTIME, ts.as_mut_ptr()) } != 0 {
+        return None;
+    }
+    // SAFETY: clock_gettime succeeded
+    let ts = unsafe { ts.assume_init() };
+
+    Some(Timestamp {
+        secs: ts.tv_sec,
+        nanos: ts.tv_nsec as u32,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deadline(secs: i64, nanos: u32) -> Timestamp {
+        Timestamp { secs, nanos }
+    }
+
+    #[test]
+    fn parse_epoch_seconds() {
+        assert_eq!(
+            deadline(1_700_000_000, 0),
+            parse_deadline("@1700000000").unwrap()
+        );
+        assert_eq!(
+            deadline(1_700_000_000, 500_000_000),
+            parse_deadline("@1700000000.5").unwrap()
+        );
+        assert!(parse_deadline("@1700000000s").is_err());
+    }
+
+    #[test]
+    fn parse_timestamps_with_offset() {
+        assert_eq!(
+            deadline(0, 0),
+            parse_deadline("1970-01-01T00:00:00Z").unwrap()
+        );
+        assert_eq!(
+            deadline(1_704_157_445, 123_000_000),
+            parse_deadline("2024-01-02T03:04:05.123+02:00").unwrap()
+        );
+        assert_eq!(
+            deadline(1_709_210_096, 0),
+            parse_deadline("2024-02-29 12:34:56-0000").unwrap()
+        );
+        assert_eq!(
+            deadline(951_782_400, 0),
+            parse_deadline("2000-02-29T00:00Z").unwrap()
+        );
+    }
+
+    #[test]
+    fn reject_invalid_times() {
+        for time in &[
+            "2023-02-29T00:00Z",
+            "2024-13-01",
+            "2024-01-01T24:00",
+            "25:00",
+            "12:60",
+            "@",
+            "@1 tomorrow",
+            "soon",
+        ] {
+            assert!(parse_deadline(time).is_err(), "{}", time);
+        }
+    }
+
+    #[test]
+    fn time_of_day_is_never_in_the_past() {
+        let now = now().unwrap();
+        for time in &["00:00", "23:59:59", "12:00 UTC"] {
+            let deadline = parse_deadline(time).unwrap();
+            assert!(deadline.secs > now.secs, "{}", time);
+            // a day may be an hour longer when daylight saving time ends
+            assert!(deadline.secs <= now.secs + 25 * 3600, "{}", time);
+        }
+    }
+}
@@ -0,0 +1,438 @@
+//! run a command with a time limit, like GNU `timeout`
+//!
+//! timeout puts itself into a new process group, so that the signal on timeout reaches the
+//! command as well as everything the command started. with `--foreground`, only the command
+//! itself is signalled, so it can keep using the terminal.
+
+use std::env;
+use std::error::Error;
+use std::io;
+use std::mem::MaybeUninit;
+use std::os::unix::process::CommandExt;
+use std::process::{exit, Command};
+use std::ptr;
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+use std::time::Duration;
+
+use common::duration::{DurationSpec, MAX_WAIT};
+use common::error::describe;
+use libc::{c_int, pid_t};
+
+mod signal;
+
+/// exit status if the command timed out
+const EXIT_TIMED_OUT: i32 = 124;
+/// exit status if timeout itself failed
+const EXIT_CANCELED: i32 = 125;
+/// exit status if the command was found but couldn't be run
+const EXIT_CANNOT_INVOKE: i32 = 126;
+/// exit status if the command wasn't found
+const EXIT_ENOENT: i32 = 127;
+
+/// signals that make timeout stop the command, in addition to the one chosen with `-s`
+const CLEANUP_SIGNALS: [c_int; 5] = [
+    libc::SIGALRM,
+    libc::SIGINT,
+    libc::SIGQUIT,
+    libc::SIGHUP,
+    libc::SIGTERM,
+];
+
+/// set by the signal handler once the timer expired
+static TIMED_OUT: AtomicBool = AtomicBool::new(false);
+/// set by the signal handler to any other signal that was received, 0 if none
+static RECEIVED: AtomicI32 = AtomicI32::new(0);
+
+type TimeoutResult<T> = Result<T, Box<dyn Error>>;
+
+const HELP: &str = "Usage: timeout [OPTION] DURATION COMMAND [ARG]...
+Start COMMAND, and kill it if still running after DURATION.
+
+  -s, --signal=SIGNAL       specify the signal to be sent on timeout;
+                              SIGNAL may be a name like 'HUP' or a number
+  -k, --kill-after=DURATION also send a KILL signal if COMMAND is still running
+                              this long after the initial signal was sent
+      --preserve-status     exit with the same status as COMMAND, even when the
+                              command times out
+      --foreground          when not running timeout directly from a shell prompt,
+                              allow COMMAND to read from the TTY and get TTY signals;
+                              in this mode, children of COMMAND will not be timed out
+  -h, --help                display this help and exit
+  -V, --version             output version information and exit
+
+DURATION is a floating point number with an optional suffix:
+'s' for seconds (the default), 'm' for minutes, 'h' for hours or 'd' for days.
+A duration of 0 disables the associated timeout.
+
+If the command times out, and --preserve-status is not set, then exit with
+status 124.  Otherwise, exit with the status of COMMAND.  If no signal
+is specified, send the TERM signal upon timeout.";
+
+/// all options and operands given by the user
+struct Options {
+    signal: c_int,
+    kill_after: Option<Duration>,
+    preserve_status: bool,
+    foreground: bool,
+    duration: Duration,
+    command: Vec<String>,
+}
+
+/// an error in the arguments, which is reported along with a hint to `--help`
+fn usage_error(msg: impl Into<String>) -> Box<dyn Error> {
+    format!("{}\nTry 'timeout --help' for more information.", msg.into()).into()
+}
+
+/// parse a duration like `sleep` does, where 0 means no timeout at all
+fn parse_duration(arg: &str) -> TimeoutResult<Option<Duration>> {
+    let duration = DurationSpec::parse(arg)
+        .map_err(|e| usage_error(e.to_string()))?
+        .to_duration();
+    Ok(Some(duration).filter(|duration| !duration.is_zero()))
+}
+
+fn parse_signal(arg: &str) -> TimeoutResult<c_int> {
+    signal::parse(arg).ok_or_else(|| usage_error(format!("'{}': invalid signal", arg)))
+}
+
+/// parse the options up to the duration, everything after it belongs to the command
+fn parse_options(args: impl IntoIterator<Item = String>) -> TimeoutResult<Options> {
+    let mut args = args.into_iter();
+    let mut signal = libc::SIGTERM;
+    let mut kill_after = None;
+    let mut preserve_status = false;
+    let mut foreground = false;
+
+    let duration = loop {
+        let arg = args.next().ok_or_else(|| usage_error("missing operand"))?;
+        let mut value = |name: &str, inline: &str| -> TimeoutResult<String> {
+            match inline {
+                "" => args.next().ok_or_else(|| {
+                    usage_error(format!("option requires an argument -- '{}'", name))
+                }),
+                _ => Ok(inline.to_owned()),
+            }
+        };
+
+        match arg.as_str() {
+            "--preserve-status" => preserve_status = true,
+            "--foreground" => foreground = true,
+            "-h" | "--help" => {
+                println!("{}", HELP);
+                exit(0);
+            }
+            "-V" | "--version" => {
+                println!("timeout {}", env!("CARGO_PKG_VERSION"));
+                exit(0);
+            }
+            "--" => break args.next().ok_or_else(|| usage_error("missing operand"))?,
+            "--signal" => signal = parse_signal(&value("signal", "")?)?,
+            "--kill-after" => kill_after = parse_duration(&value("kill-after", "")?)?,
+            _ if arg.starts_with("--signal=") => signal = parse_signal(&arg[9..])?,
+            _ if arg.starts_with("--kill-after=") => kill_after = parse_duration(&arg[13..])?,
+            _ if arg.starts_with("-s") => signal = parse_signal(&value("s", &arg[2..])?)?,
+            _ if arg.starts_with("-k") => kill_after = parse_duration(&value("k", &arg[2..])?)?,
+            _ if arg.starts_with('-') && arg.len() > 1 => {
+                return Err(usage_error(format!("unrecognized option '{}'", arg)))
+            }
+            _ => break arg,
+        }
+    };
+
+    let duration = parse_duration(&duration)?.unwrap_or(Duration::ZERO);
+    let command: Vec<String> = args.collect();
+    if command.is_empty() {
+        return Err(usage_error("missing operand"));
+    }
+
+    Ok(Options {
+        signal,
+        kill_after,
+        preserve_status,
+        foreground,
+        duration,
+        command,
+    })
+}
+
+extern "C" fn on_signal(sig: c_int) {
+    match sig {
+        libc::SIGALRM => TIMED_OUT.store(true, Ordering::SeqCst),
+        // only wakes up the main loop, which then collects the exit status
+        libc::SIGCHLD => {}
+        _ => RECEIVED.store(sig, Ordering::SeqCst),
+    }
+}
+
+/// catch the signal with [`on_signal`]. system calls are interrupted rather than restarted, so
+/// the main loop notices the signal right away
+fn install_handler(sig: c_int) -> io::Result<()> {
+    // SAFETY: a zeroed sigaction is valid, and the handler only stores into atomics, which is
+    // async-signal-safe
+    let result = unsafe {
+        let mut action: libc::sigaction = MaybeUninit::zeroed().assume_init();
+        action.sa_sigaction = on_signal as extern "C" fn(c_int) as libc::sighandler_t;
+        libc::sigemptyset(&mut action.sa_mask);
+        libc::sigaction(sig, &action, ptr::null_mut())
+    };
+
+    match result {
+        0 => Ok(()),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+/// let the timer send SIGALRM once the duration has passed. a zero duration turns it off
+fn set_timer(duration: Duration) -> io::Result<()> {
+    let micros = duration.min(MAX_WAIT).as_nanos().div_ceil(1000);
+    let timer = libc::itimerval {
+        it_interval: libc::timeval {
+            tv_sec: 0,
+            tv_usec: 0,
+        },
+        it_value: libc::timeval {
+            tv_sec: (micros / 1_000_000) as libc::time_t,
+            tv_usec: (micros % 1_000_000) as libc::suseconds_t,
+        },
+    };
+
+    // SAFETY: the timer value is valid and the old value isn't requested
+    match unsafe { libc::setitimer(libc::ITIMER_REAL, &timer, ptr::null_mut()) } {
+        0 => Ok(()),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+/// send the signal to the command, and unless in the foreground also to the rest of the process
+/// group, so processes started by the command are stopped as well. in that case stopped processes
+/// are woken up, so they can handle the signal. in the foreground, the command is left stopped like
+/// GNU does, as it may have been stopped from the terminal on purpose
+fn send_signal(options: &Options, child: pid_t, sig: c_int) {
+    // SAFETY: sending signals has no memory safety requirements. failures are ignored, as the
+    // processes may have exited already
+    unsafe {
+        libc::kill(child, sig);
+        if !options.foreground {
+            // timeout itself is in the same process group, but must keep running to report the
+            // exit status of the command
+            libc::signal(sig, libc::SIG_IGN);
+            libc::kill(0, sig);
+
+            if sig != libc::SIGKILL && sig != libc::SIGCONT {
+                libc::kill(child, libc::SIGCONT);
+                libc::kill(0, libc::SIGCONT);
+            }
+        }
+    }
+}
+
+/// wait for the command to exit, stopping it once the time is up or timeout is asked to stop.
+/// returns the raw wait status and whether the command timed out
+fn supervise(options: &Options, child: pid_t) -> io::Result<(c_int, bool)> {
+    let mut signal = options.signal;
+    let mut kill_after = options.kill_after;
+    let mut timed_out = false;
+
+    // signals are only let through while waiting for them, otherwise one could arrive just
+    // before waiting and be missed
+    let mut blocked = MaybeUninit::uninit();
+    let mut waiting = MaybeUninit::uninit();
+    // SAFETY: the sets are initialized by sigemptyset and sigprocmask before they are used
+    let waiting = unsafe {
+        libc::sigemptyset(blocked.as_mut_ptr());
+        for &sig in CLEANUP_SIGNALS.iter().chain(&[libc::SIGCHLD, signal]) {
+            libc::sigaddset(blocked.as_mut_ptr(), sig);
+        }
+        libc::sigprocmask(libc::SIG_BLOCK, blocked.as_ptr(), waiting.as_mut_ptr());
+        let mut waiting = waiting.assume_init();
+        for &sig in CLEANUP_SIGNALS.iter().chain(&[libc::SIGCHLD, signal]) {
+            libc::sigdelset(&mut waiting, sig);
+        }
+        waiting
+    };
+
+    loop {
+        let mut status = 0;
+        // SAFETY: the status is a valid pointer
+        match unsafe { libc::waitpid(child, &mut status, libc::WNOHANG) } {
+            0 => {}
+            -1 => match io::Error::last_os_error() {
+                e if e.kind() == io::ErrorKind::Interrupted => continue,
+                e => return Err(e),
+            },
+            _ => return Ok((status, timed_out)),
+        }
+
+        let sig = if TIMED_OUT.swap(false, Ordering::SeqCst) {
+            timed_out = true;
+            Some(signal)
+        } else {
+            Some(RECEIVED.swap(0, Ordering::SeqCst)).filter(|&sig| sig != 0)
+        };
+
+        if let Some(sig) = sig {
+            send_signal(options, child, sig);
+            // if the command doesn't give up in time, the next signal kills it for sure
+            if let Some(kill_after) = kill_after.take() {
+                signal = libc::SIGKILL;
+                set_timer(kill_after)?;
+            }
+        }
+
+        // SAFETY: the set was initialized above
+        unsafe { libc::sigsuspend(&waiting) };
+    }
+}
+
+/// die from the same signal as the command did, so the caller sees the same wait status. core
+/// dumps are turned off first, as they would be about timeout rather than the command. returns
+/// if the signal didn't end the process
+fn raise_signal(sig: c_int) {
+    let no_core = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+
+    // SAFETY: the limit is valid, and the signal is unblocked only after its handler is reset
+    unsafe {
+        if libc::setrlimit(libc::RLIMIT_CORE, &no_core) != 0 {
+            return;
+        }
+        libc::signal(sig, libc::SIG_DFL);
+        let mut set = MaybeUninit::uninit();
+        libc::sigemptyset(set.as_mut_ptr());
+        libc::sigaddset(set.as_mut_ptr(), sig);
+        libc::sigprocmask(libc::SIG_UNBLOCK, set.as_ptr(), ptr::null_mut());
+        libc::raise(sig);
+    }
+}
+
+/// run the command and return the exit status of timeout
+fn run(options: &Options) -> TimeoutResult<i32> {
+    if !options.foreground {
+        // SAFETY: moving into a new process group has no memory safety requirements. this
+        // fails if timeout already leads a process group, which is fine as well
+        unsafe { libc::setpgid(0, 0) };
+    }
+
+    for &sig in CLEANUP_SIGNALS.iter().chain(&[libc::SIGCHLD]) {
+        install_handler(sig)?;
+    }
+    if options.signal != libc::SIGKILL && options.signal != libc::SIGSTOP && options.signal != 0 {
+        install_handler(options.signal)?;
+    }
+    // SAFETY: ignoring signals has no memory safety requirements. in its own process group,
+    // timeout runs in the background and would be stopped when the command uses the terminal
+    unsafe {
+        libc::signal(libc::SIGTTIN, libc::SIG_IGN);
+        libc::signal(libc::SIGTTOU, libc::SIG_IGN);
+    }
+
+    let mut command = Command::new(&options.command[0]);
+    command.args(&options.command[1..]);
+    // SAFETY: only async-signal-safe functions are called between fork and exec. ignored
+    // signals are inherited, but the command should be stopped as usual
+    unsafe {
+        command.pre_exec(|| {
+            libc::signal(libc::SIGTTIN, libc::SIG_DFL);
+            libc::signal(libc::SIGTTOU, libc::SIG_DFL);
+            Ok(())
+        });
+    }
+
+    let child = match command.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            eprintln!(
+                "timeout: failed to run command '{}': {}",
+                options.command[0],
+                describe(&e)
+            );
+            return Ok(match e.kind() {
+                io::ErrorKind::NotFound => EXIT_ENOENT,
+                _ => EXIT_CANNOT_INVOKE,
+            });
+        }
+    };
+
+    set_timer(options.duration)?;
+    let (status, timed_out) = supervise(options, child.id() as pid_t)?;
+
+    let mut preserve_status = options.preserve_status;
+    let status = if libc::WIFEXITED(status) {
+        libc::WEXITSTATUS(status)
+    } else if libc::WIFSIGNALED(status) {
+        let sig = libc::WTERMSIG(status);
+        if !timed_out {
+            raise_signal(sig);
+        }
+        // a forced kill is reported as such, so it can be told apart from a regular timeout
+        if timed_out && sig == libc::SIGKILL {
+            preserve_status = true;
+        }
+        128 + sig
+    } else {
+        // the command was stopped or continued, which waitpid doesn't report without WUNTRACED
+        EXIT_CANCELED
+    };
+
+    Ok(if timed_out && !preserve_status {
+        EXIT_TIMED_OUT
+    } else {
+        status
+    })
+}
+
+fn main() {
+    let result = parse_options(env::args().skip(1)).and_then(|options| run(&options));
+
+    match result {
+        Ok(status) => exit(status),
+        Err(e) => {
+            eprintln!("timeout: {}", e);
+            exit(EXIT_CANCELED)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> TimeoutResult<Options> {
+        parse_options(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn parse_options_before_command() {
+        let options =
+            parse(&["-s", "KILL", "-k5", "--preserve-status", "1.5", "ls", "-l"]).unwrap();
+        assert_eq!(libc::SIGKILL, options.signal);
+        assert_eq!(Some(Duration::from_secs(5)), options.kill_after);
+        assert!(options.preserve_status);
+        assert!(!options.foreground);
+        assert_eq!(Duration::from_millis(1500), options.duration);
+        assert_eq!(vec!["ls", "-l"], options.command);
+
+        let options = parse(&["--signal=INT", "--kill-after", "0", "--", "1m", "-s"]).unwrap();
+        assert_eq!(libc::SIGINT, options.signal);
+        assert_eq!(None, options.kill_after);
+        assert_eq!(Duration::from_secs(60), options.duration);
+        assert_eq!(vec!["-s"], options.command);
+    }
+
+    #[test]
+    fn reject_invalid_arguments() {
+        for args in &[
+            &[][..],
+            &["1"],
+            &["-s", "NOPE", "1", "ls"],
+            &["-k", "1x", "1", "ls"],
+            &["1x", "ls"],
+            &["--bogus", "1", "ls"],
+            &["-s"],
+        ] {
+            assert!(parse(args).is_err(), "{:?}", args);
+        }
+    }
+}
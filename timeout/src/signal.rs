@@ -0,0 +1,77 @@
+//! names of signals, as given with `-s`
+
+use libc::c_int;
+
+/// all signals that can be given by name, without the `SIG` prefix
+const SIGNALS: &[(&str, c_int)] = &[
+    ("HUP", libc::SIGHUP),
+    ("INT", libc::SIGINT),
+    ("QUIT", libc::SIGQUIT),
+    ("ILL", libc::SIGILL),
+    ("TRAP", libc::SIGTRAP),
+    ("ABRT", libc::SIGABRT),
+    ("IOT", libc::SIGIOT),
+    ("BUS", libc::SIGBUS),
+    ("FPE", libc::SIGFPE),
+    ("KILL", libc::SIGKILL),
+    ("USR1", libc::SIGUSR1),
+    ("SEGV", libc::SIGSEGV),
+    ("USR2", libc::SIGUSR2),
+    ("PIPE", libc::SIGPIPE),
+    ("ALRM", libc::SIGALRM),
+    ("TERM", libc::SIGTERM),
+    ("CHLD", libc::SIGCHLD),
+    ("CONT", libc::SIGCONT),
+    ("STOP", libc::SIGSTOP),
+    ("TSTP", libc::SIGTSTP),
+    ("TTIN", libc::SIGTTIN),
+    ("TTOU", libc::SIGTTOU),
+    ("URG", libc::SIGURG),
+    ("XCPU", libc::SIGXCPU),
+    ("XFSZ", libc::SIGXFSZ),
+    ("VTALRM", libc::SIGVTALRM),
+    ("PROF", libc::SIGPROF),
+    ("WINCH", libc::SIGWINCH),
+    ("IO", libc::SIGIO),
+    ("SYS", libc::SIGSYS),
+];
+
+/// parse a signal given as number like `9` or as name like `KILL` or `SIGKILL`
+pub fn parse(signal: &str) -> Option<c_int> {
+    if let Ok(number) = signal.parse() {
+        // 0 isn't a real signal, but it's valid for kill and checks whether the process exists
+        return if (0..=max_signal()).contains(&number) {
+            Some(number)
+        } else {
+            None
+        };
+    }
+
+    let name = signal.strip_prefix("SIG").unwrap_or(signal);
+    SIGNALS
+        .iter()
+        .find(|(candidate, _)| *candidate == name)
+        .map(|&(_, number)| number)
+}
+
+/// highest signal number, including the real-time signals
+fn max_signal() -> c_int {
+    libc::SIGRTMAX()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_names_and_numbers() {
+        assert_eq!(Some(libc::SIGKILL), parse("KILL"));
+        assert_eq!(Some(libc::SIGKILL), parse("SIGKILL"));
+        assert_eq!(Some(libc::SIGKILL), parse("9"));
+        assert_eq!(Some(0), parse("0"));
+        assert_eq!(Some(libc::SIGRTMAX()), parse(&libc::SIGRTMAX().to_string()));
+        for signal in &["kill", "SIG", "NOPE", "-1", "", "999"] {
+            assert_eq!(None, parse(signal), "{}", signal);
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use std::os::unix::process::ExitStatusExt;
+use std::process::{Command, ExitStatus, Output};
+use std::time::{Duration, Instant};
+
+fn timeout(args: &[&str]) -> (Output, Duration) {
+    let start = Instant::now();
+    let output = Command::new(env!("CARGO_BIN_EXE_timeout"))
+        .args(args)
+        .output()
+        .unwrap();
+    (output, start.elapsed())
+}
+
+/// the exit status as a shell reports it. timeout may be killed along with the command's
+/// process group, when it's sent the KILL signal
+fn code(status: ExitStatus) -> i32 {
+    status
+        .code()
+        .or_else(|| status.signal().map(|sig| 128 + sig))
+        .unwrap()
+}
+
+fn status(args: &[&str]) -> i32 {
+    code(timeout(args).0.status)
+}
+
+#[test]
+fn passes_exit_status_through() {
+    assert_eq!(0, status(&["10", "true"]));
+    assert_eq!(3, status(&["10", "sh", "-c", "exit 3"]));
+    assert_eq!(0, status(&["0", "sh", "-c", "sleep 0.1"]));
+}
+
+#[test]
+fn stops_command_on_timeout() {
+    let (output, elapsed) = timeout(&["0.2", "sleep", "10"]);
+    assert_eq!(Some(124), output.status.code());
+    assert!(elapsed < Duration::from_secs(5), "{:?}", elapsed);
+
+    assert_eq!(
+        128 + 15,
+        status(&["--preserve-status", "0.2", "sleep", "10"])
+    );
+    assert_eq!(128 + 9, status(&["-s", "KILL", "0.2", "sleep", "10"]));
+}
+
+#[test]
+fn kills_command_that_ignores_signal() {
+    let (output, elapsed) = timeout(&["-k", "0.3", "0.2", "sh", "-c", "trap '' TERM; sleep 10"]);
+    assert_eq!(128 + 9, code(output.status));
+    assert!(elapsed < Duration::from_secs(5), "{:?}", elapsed);
+}
+
+#[test]
+fn signals_whole_process_group() {
+    // the background sleep would keep the output pipe open if it survived the timeout
+    let (output, elapsed) = timeout(&["0.2", "sh", "-c", "sleep 10 & wait"]);
+    assert_eq!(Some(124), output.status.code());
+    assert!(elapsed < Duration::from_secs(5), "{:?}", elapsed);
+}
+
+#[test]
+fn foreground_only_signals_command() {
+    let (output, elapsed) = timeout(&["--foreground", "0.2", "sleep", "10"]);
+    assert_eq!(Some(124), output.status.code());
+    assert!(elapsed < Duration::from_secs(5), "{:?}", elapsed);
+}
+
+#[test]
+fn reports_own_failures() {
+    let (output, _) = timeout(&["1x", "true"]);
+    assert_eq!(Some(125), output.status.code());
+    assert_eq!(
+        "timeout: invalid time interval '1x'\nTry 'timeout --help' for more information.\n",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    assert_eq!(125, status(&["-s", "NOPE", "1", "true"]));
+    let (output, _) = timeout(&["1", "/nonexistent/command"]);
+    assert_eq!(Some(127), output.status.code());
+    assert_eq!(
+        "timeout: failed to run command '/nonexistent/command': No such file or directory\n",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(126, status(&["1", "/"]));
+}
+
+#[test]
+fn ignores_terminal_signals_but_command_does_not() {
+    // the ignored signals of timeout, which is the parent of the shell, and of the shell itself
+    let (output, _) = timeout(&[
+        "10",
+        "sh",
+        "-c",
+        "grep SigIgn /proc/$PPID/status /proc/$$/status",
+    ]);
+    let masks = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| u64::from_str_radix(line.rsplit('\t').next().unwrap(), 16).unwrap())
+        .collect::<Vec<_>>();
+
+    // SIGTTIN and SIGTTOU are signals 21 and 22
+    let terminal = 0b11 << 20;
+    assert_eq!(2, masks.len(), "{:?}", output);
+    assert_eq!(terminal, masks[0] & terminal);
+    assert_eq!(0, masks[1] & terminal);
+}
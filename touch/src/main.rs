@@ -1,36 +1,442 @@
-use anyhow::Result;
+use anyhow::{anyhow, bail, Result};
+use common::date::{self, Timestamp};
+use common::error::describe;
 use filetime::FileTime;
 use std::env;
-use std::fs::OpenOptions;
+use std::ffi::CString;
+use std::fs::{self, OpenOptions};
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::OpenOptionsExt;
 use std::path::Path;
 use std::process;
 
-fn main() -> Result<()> {
-    let path = env::args().nth(1).unwrap_or_else(|| {
+mod stamp;
+
+/// Options given on the command line, along with the files to touch.
+struct Options {
+    /// Change the access time, set by `-a` or `--time=atime`.
+    access: bool,
+    /// Change the modification time, set by `-m` or `--time=mtime`.
+    modification: bool,
+    /// Don't create missing files, set by `-c` or `--no-create`.
+    no_create: bool,
+    /// Change symbolic links themselves instead of the files they point to, set by `-h` or
+    /// `--no-dereference`. Missing files are never created then.
+    no_dereference: bool,
+    /// File to take the times from instead of the current time, set by `-r` or `--reference`.
+    reference: Option<String>,
+    /// Timestamp to use instead of the current time, set by `-t`.
+    stamp: Option<String>,
+    /// Date string to use instead of the current time, set by `-d` or `--date`. Along with a
+    /// reference file, it's relative to that file's times.
+    date: Option<String>,
+    files: Vec<String>,
+}
+
+/// The times that are given to the files.
+#[derive(Clone, Copy)]
+struct Times {
+    atime: Time,
+    mtime: Time,
+}
+
+/// A time to give to a file.
+#[derive(Clone, Copy)]
+enum Time {
+    /// The current time, which is left to the system to fill in. Like in GNU touch, that only
+    /// requires permission to write to the file rather than owning it.
+    Now,
+    At(FileTime),
+}
+
+fn main() {
+    let options = parse_args(env::args().skip(1)).unwrap_or_else(|e| {
+        eprintln!("touch: {}\nTry 'touch --help' for more information.", e);
+        process::exit(1);
+    });
+
+    let options = options.unwrap_or_else(|| {
         print_usage();
         process::exit(1);
     });
-    create_if_not_exists(&path)?;
-    set_time(path, FileTime::now())?;
-    Ok(())
+
+    let times = initial_times(&options).unwrap_or_else(|e| {
+        eprintln!("touch: {}", e);
+        process::exit(1);
+    });
+    let mut failed = false;
+
+    for file in &options.files {
+        if let Err(e) = touch(&options, file, times) {
+            eprintln!("touch: {}", e);
+            failed = true;
+        }
+    }
+
+    if failed {
+        process::exit(1);
+    }
 }
 
-fn print_usage() {
-    eprintln!(
-        r#"touch 0.1.0
+const USAGE: &str = r#"touch 0.1.0
 touch - change file timestamps
 
 USAGE:
-    touch <file>"#
-    );
+    touch [OPTIONS] <file>...
+
+OPTIONS:
+    -a                  change only the access time
+    -c, --no-create     do not create any files
+    -d, --date=STRING   parse STRING and use it instead of current time
+    -h, --no-dereference
+                        affect each symbolic link instead of any referenced file
+    -m                  change only the modification time
+    -r, --reference=FILE
+                        use this file's times instead of current time
+    -t STAMP            use [[CC]YY]MMDDhhmm[.ss] instead of current time
+    --time=WORD         change the specified time:
+                        WORD is access, atime, or use: equivalent to -a
+                        WORD is modify or mtime: equivalent to -m
+    --help              display this help and exit
+    --version           output version information and exit
+
+A file argument of - changes the times of the file open on standard output."#;
+
+fn print_usage() {
+    eprintln!("{}", USAGE);
+}
+
+/// A single option, which may be given by its short or long name.
+#[derive(Clone, Copy)]
+enum Opt {
+    Access,
+    Modification,
+    NoCreate,
+    NoDereference,
+    Date,
+    Reference,
+    Stamp,
+    Time,
+    Help,
+    Version,
+}
+
+impl Opt {
+    fn from_short(flag: char) -> Option<Self> {
+        match flag {
+            'a' => Some(Opt::Access),
+            'm' => Some(Opt::Modification),
+            'c' => Some(Opt::NoCreate),
+            'd' => Some(Opt::Date),
+            'h' => Some(Opt::NoDereference),
+            'r' => Some(Opt::Reference),
+            't' => Some(Opt::Stamp),
+            _ => None,
+        }
+    }
+
+    fn from_long(name: &str) -> Option<Self> {
+        match name {
+            "no-create" => Some(Opt::NoCreate),
+            "no-dereference" => Some(Opt::NoDereference),
+            "date" => Some(Opt::Date),
+            "reference" => Some(Opt::Reference),
+            "time" => Some(Opt::Time),
+            "help" => Some(Opt::Help),
+            "version" => Some(Opt::Version),
+            _ => None,
+        }
+    }
+
+    fn takes_value(self) -> bool {
+        matches!(self, Opt::Date | Opt::Reference | Opt::Stamp | Opt::Time)
+    }
+}
+
+/// Parse the arguments, returning `None` if no file was given.
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Option<Options>> {
+    let mut args = args.into_iter();
+    let mut options = Options {
+        access: false,
+        modification: false,
+        no_create: false,
+        no_dereference: false,
+        reference: None,
+        stamp: None,
+        date: None,
+        files: Vec::new(),
+    };
+
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            options.files.extend(&mut args);
+        } else if let Some(long) = arg.strip_prefix("--") {
+            let (name, value) = match long.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_owned())),
+                None => (long, None),
+            };
+            let opt =
+                Opt::from_long(name).ok_or_else(|| anyhow!("unrecognized option '{}'", arg))?;
+            let value = match (opt.takes_value(), value) {
+                (true, value) => Some(
+                    value
+                        .or_else(|| args.next())
+                        .ok_or_else(|| anyhow!("option '--{}' requires an argument", name))?,
+                ),
+                (false, None) => None,
+                (false, Some(_)) => bail!("option '--{}' doesn't allow an argument", name),
+            };
+            apply(&mut options, opt, value)?;
+        } else if arg.len() > 1 && arg.starts_with('-') {
+            for (i, flag) in arg.char_indices().skip(1) {
+                let opt =
+                    Opt::from_short(flag).ok_or_else(|| anyhow!("invalid option -- '{}'", flag))?;
+                if !opt.takes_value() {
+                    apply(&mut options, opt, None)?;
+                    continue;
+                }
+
+                // The value is either the rest of this argument or the next one.
+                let rest = &arg[i + flag.len_utf8()..];
+                let value = match rest {
+                    "" => args
+                        .next()
+                        .ok_or_else(|| anyhow!("option requires an argument -- '{}'", flag))?,
+                    _ => rest.to_owned(),
+                };
+                apply(&mut options, opt, Some(value))?;
+                break;
+            }
+        } else {
+            options.files.push(arg);
+        }
+    }
+
+    if options.stamp.is_some() && (options.reference.is_some() || options.date.is_some()) {
+        bail!("cannot specify times from more than one source");
+    }
+
+    // Without -a or -m, both times are changed.
+    if !options.access && !options.modification {
+        options.access = true;
+        options.modification = true;
+    }
+
+    if options.files.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(options))
 }
 
-fn create_if_not_exists<P: AsRef<Path>>(file: &P) -> Result<()> {
-    OpenOptions::new().create(true).write(true).open(file)?;
+/// Apply a single option, along with its value if it takes one.
+fn apply(options: &mut Options, opt: Opt, value: Option<String>) -> Result<()> {
+    match opt {
+        Opt::Access => options.access = true,
+        Opt::Modification => options.modification = true,
+        Opt::NoCreate => options.no_create = true,
+        Opt::NoDereference => options.no_dereference = true,
+        Opt::Date => options.date = value,
+        Opt::Reference => options.reference = value,
+        Opt::Stamp => options.stamp = value,
+        Opt::Time => match parse_time_word(value.as_deref().unwrap_or_default())? {
+            TimeWord::Access => options.access = true,
+            TimeWord::Modification => options.modification = true,
+        },
+        Opt::Help => {
+            println!("{}", USAGE);
+            process::exit(0);
+        }
+        Opt::Version => {
+            println!("touch {}", env!("CARGO_PKG_VERSION"));
+            process::exit(0);
+        }
+    }
     Ok(())
 }
 
-fn set_time<P: AsRef<Path>>(p: P, time: FileTime) -> Result<()> {
-    filetime::set_file_times(p, time, time)?;
+/// The timestamp selected by `--time`.
+enum TimeWord {
+    Access,
+    Modification,
+}
+
+fn parse_time_word(word: &str) -> Result<TimeWord> {
+    match word {
+        "access" | "atime" | "use" => Ok(TimeWord::Access),
+        "modify" | "mtime" => Ok(TimeWord::Modification),
+        _ => bail!(
+            "invalid argument '{}' for '--time'\n\
+             Valid arguments are:\n  \
+             - 'atime', 'access', 'use'\n  \
+             - 'mtime', 'modify'",
+            word
+        ),
+    }
+}
+
+/// Get the times to give to the files, which are either those of the reference file, the
+/// timestamp given with `-t` or the current time, possibly adjusted by the date string given
+/// with `-d`. Unless it's only the current time, all files get the same times, no matter how
+/// long touching them takes.
+fn initial_times(options: &Options) -> Result<Times> {
+    if options.reference.is_none() && options.stamp.is_none() && options.date.is_none() {
+        return Ok(Times {
+            atime: Time::Now,
+            mtime: Time::Now,
+        });
+    }
+
+    let (atime, mtime) = match &options.reference {
+        Some(reference) => {
+            let meta = if options.no_dereference {
+                fs::symlink_metadata(reference)
+            } else {
+                fs::metadata(reference)
+            };
+            let meta = meta.map_err(|e| {
+                anyhow!(
+                    "failed to get attributes of '{}': {}",
+                    reference,
+                    describe(&e)
+                )
+            })?;
+            (
+                FileTime::from_last_access_time(&meta),
+                FileTime::from_last_modification_time(&meta),
+            )
+        }
+        None => {
+            let time = match &options.stamp {
+                Some(stamp) => stamp::parse(stamp)
+                    .map(|secs| FileTime::from_unix_time(secs, 0))
+                    .ok_or_else(|| anyhow!("invalid date format '{}'", stamp))?,
+                None => FileTime::now(),
+            };
+            (time, time)
+        }
+    };
+
+    let (atime, mtime) = match &options.date {
+        Some(date) => {
+            // Relative items like `-d '1 hour ago'` apply to each time on its own.
+            let parse = |base: FileTime| {
+                let base = Timestamp {
+                    secs: base.unix_seconds(),
+                    nanos: base.nanoseconds(),
+                };
+                date::parse(date, base)
+                    .map(|time| FileTime::from_unix_time(time.secs, time.nanos))
+                    .ok_or_else(|| anyhow!("invalid date format '{}'", date))
+            };
+            (parse(atime)?, parse(mtime)?)
+        }
+        None => (atime, mtime),
+    };
+
+    Ok(Times {
+        atime: Time::At(atime),
+        mtime: Time::At(mtime),
+    })
+}
+
+/// Touch a single file, creating it first unless `-c` or `-h` was given. The file `-` stands for
+/// standard output, which is never created.
+fn touch(options: &Options, file: &str, times: Times) -> Result<()> {
+    let stdout = file == "-";
+
+    // Like GNU touch, failing to create the file is only reported if its times can't be set
+    // either. That way directories and other files that can't be opened for writing still work.
+    let created = if options.no_create || options.no_dereference || stdout {
+        Ok(())
+    } else {
+        create_if_not_exists(&file)
+    };
+
+    let atime = Some(times.atime).filter(|_| options.access);
+    let mtime = Some(times.mtime).filter(|_| options.modification);
+    let result = if stdout {
+        set_stdout_time(atime, mtime)
+    } else {
+        set_time(file, atime, mtime, !options.no_dereference)
+    };
+
+    match (result, created) {
+        (Ok(()), _) => Ok(()),
+        (Err(e), _) if options.no_create && e.kind() == io::ErrorKind::NotFound => Ok(()),
+        (Err(_), Err(e)) => Err(anyhow!("cannot touch '{}': {}", file, describe(&e))),
+        (Err(e), Ok(())) => Err(anyhow!("setting times of '{}': {}", file, describe(&e))),
+    }
+}
+
+/// Create the file if it doesn't exist, or the file that a dangling symbolic link points to.
+/// Existing files are never opened, so FIFOs, terminals and devices are left alone. Should one
+/// appear in the meantime, opening it still neither blocks nor makes it the controlling terminal.
+fn create_if_not_exists<P: AsRef<Path>>(file: &P) -> io::Result<()> {
+    if fs::metadata(file).is_ok() {
+        return Ok(());
+    }
+    OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .custom_flags(libc::O_NONBLOCK | libc::O_NOCTTY)
+        .open(file)?;
     Ok(())
 }
+
+/// Set the given times of the file, or of a symbolic link itself unless `follow` is set. A time
+/// that is `None` is left as it is, so the other time is not touched at all.
+///
+/// This calls utimensat directly, as filetime opens the file to set its times, which blocks on
+/// a FIFO and fails on files that can't be read or written.
+fn set_time<P: AsRef<Path>>(
+    p: P,
+    atime: Option<Time>,
+    mtime: Option<Time>,
+    follow: bool,
+) -> io::Result<()> {
+    let path = CString::new(p.as_ref().as_os_str().as_bytes())?;
+    let times = timespecs(atime, mtime);
+    let flags = if follow { 0 } else { libc::AT_SYMLINK_NOFOLLOW };
+
+    // SAFETY: the path is NUL-terminated and there are two times, as utimensat expects.
+    let result = unsafe { libc::utimensat(libc::AT_FDCWD, path.as_ptr(), times.as_ptr(), flags) };
+    if result == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+/// Like [`set_time`], but changes whatever file is open on standard output.
+fn set_stdout_time(atime: Option<Time>, mtime: Option<Time>) -> io::Result<()> {
+    let times = timespecs(atime, mtime);
+
+    // SAFETY: there are two times, as futimens expects.
+    if unsafe { libc::futimens(libc::STDOUT_FILENO, times.as_ptr()) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+/// The times as utimensat and futimens take them, where `None` leaves a time as it is.
+fn timespecs(atime: Option<Time>, mtime: Option<Time>) -> [libc::timespec; 2] {
+    let timespec = |time: Option<Time>| match time {
+        Some(Time::At(time)) => libc::timespec {
+            tv_sec: time.unix_seconds(),
+            tv_nsec: time.nanoseconds().into(),
+        },
+        Some(Time::Now) => libc::timespec {
+            tv_sec: 0,
+            tv_nsec: libc::UTIME_NOW,
+        },
+        None => libc::timespec {
+            tv_sec: 0,
+            tv_nsec: libc::UTIME_OMIT,
+        },
+    };
+    [timespec(atime), timespec(mtime)]
+}
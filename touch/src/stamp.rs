@@ -0,0 +1,53 @@
+//! POSIX timestamps as given with `-t`, in the form `[[CC]YY]MMDDhhmm[.ss]`.
+
+use common::localtime::{self, DateTime};
+use filetime::FileTime;
+
+/// Parse a timestamp in local time into seconds since the epoch. Without a century, years from
+/// 69 to 99 are in the 20th century and years from 00 to 68 in the 21st. Without a year, the
+/// current one is used. Returns `None` if the stamp is malformed or names a time that doesn't
+/// exist.
+pub fn parse(stamp: &str) -> Option<i64> {
+    let (digits, seconds) = match stamp.split_once('.') {
+        Some((digits, seconds)) => (digits, Some(seconds)),
+        None => (stamp, None),
+    };
+
+    if !digits.bytes().all(|c| c.is_ascii_digit())
+        || !seconds.is_none_or(|s| s.len() == 2 && s.bytes().all(|c| c.is_ascii_digit()))
+    {
+        return None;
+    }
+
+    let number = |i: usize| digits[i..i + 2].parse::<u32>().ok();
+    let (year, rest) = match digits.len() {
+        8 => (localtime::to_local(FileTime::now().unix_seconds())?.year, 0),
+        10 => match number(0)? {
+            year @ 69..=99 => (1900 + i64::from(year), 2),
+            year => (2000 + i64::from(year), 2),
+        },
+        12 => (i64::from(number(0)? * 100 + number(2)?), 4),
+        _ => return None,
+    };
+
+    let datetime = DateTime {
+        year,
+        month: number(rest)?,
+        day: number(rest + 2)?,
+        hour: number(rest + 4)?,
+        minute: number(rest + 6)?,
+        second: seconds.map_or(Some(0), |s| s.parse().ok())?,
+    };
+
+    // POSIX allows a leap second even where the time zone doesn't know about them. It stands
+    // for the second after 59, which may well be in the next day or year.
+    if datetime.second == 60 {
+        let secs = localtime::from_local(DateTime {
+            second: 59,
+            ..datetime
+        })?;
+        return Some(secs + 1);
+    }
+
+    localtime::from_local(datetime)
+}
@@ -1,72 +1,612 @@
+//! Conformance tests for touch, with the expected results taken from GNU coreutils. Every test
+//! works in a temporary directory of its own, and dates are in UTC unless a test sets `TZ`.
+
 use anyhow::Result;
-use assert_cmd::prelude::*;
+use assert_cmd::Command;
 use filetime::FileTime;
 use predicates::str::contains;
-use std::env;
-use std::fs;
-use std::fs::File;
-use std::path::Path;
-use std::process::Command;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::fs::{self, File};
+use std::io;
+use std::os::unix::fs::{symlink, PermissionsExt};
+use std::os::unix::process::CommandExt;
+use std::path::PathBuf;
+use std::process;
+use std::time::Duration;
+use tempfile::TempDir;
+
+/// A temporary directory to run touch in.
+struct Dir(TempDir);
+
+impl Dir {
+    /// Create the directory with a file `f` that has the [`old`] times, and a file `ref` that
+    /// has the [`reference`] times.
+    fn new() -> Result<Self> {
+        let dir = Dir(TempDir::new()?);
+        dir.file("f", old())?;
+        dir.file("ref", reference())?;
+        Ok(dir)
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.0.path().join(name)
+    }
+
+    fn file(&self, name: &str, (atime, mtime): (FileTime, FileTime)) -> Result<()> {
+        File::create(self.path(name))?;
+        filetime::set_file_times(self.path(name), atime, mtime)?;
+        Ok(())
+    }
+
+    fn exists(&self, name: &str) -> bool {
+        fs::symlink_metadata(self.path(name)).is_ok()
+    }
+
+    /// The access and modification time of a file, following symbolic links.
+    fn times(&self, name: &str) -> Result<(FileTime, FileTime)> {
+        let meta = fs::metadata(self.path(name))?;
+        Ok((
+            FileTime::from_last_access_time(&meta),
+            FileTime::from_last_modification_time(&meta),
+        ))
+    }
+
+    /// The access and modification time of a symbolic link itself.
+    fn link_times(&self, name: &str) -> Result<(FileTime, FileTime)> {
+        let meta = fs::symlink_metadata(self.path(name))?;
+        Ok((
+            FileTime::from_last_access_time(&meta),
+            FileTime::from_last_modification_time(&meta),
+        ))
+    }
+
+    /// Prepare to run touch with the given arguments inside the directory.
+    fn touch(&self, args: &[&str]) -> Command {
+        let mut command = Command::cargo_bin("touch").unwrap();
+        command
+            .current_dir(self.0.path())
+            .env("TZ", "UTC0")
+            .args(args);
+        command
+    }
+}
+
+fn at(secs: i64, nanos: u32) -> FileTime {
+    FileTime::from_unix_time(secs, nanos)
+}
+
+/// The times of files before touching them, long ago.
+fn old() -> (FileTime, FileTime) {
+    (at(1_000_000_000, 0), at(1_100_000_000, 0))
+}
+
+/// The times of the reference file, with nanoseconds.
+fn reference() -> (FileTime, FileTime) {
+    (at(1_200_000_000, 500_000_000), at(1_300_000_000, 250))
+}
 
 #[test]
-fn test_help_message_when_no_argument_passed() -> Result<()> {
-    Command::cargo_bin("touch")?
-        .assert()
-        .failure()
-        .stderr(contains("USAGE"));
+fn sets_given_times() -> Result<()> {
+    let (old, reference) = (old(), reference());
+    let new = at(5000, 0);
+    let both = |secs, nanos| (at(secs, nanos), at(secs, nanos));
+
+    let cases: &[(&[&str], (FileTime, FileTime))] = &[
+        (&["-d", "@5000", "f"], (new, new)),
+        (&["-d@5000", "f"], (new, new)),
+        (&["--date=@5000", "f"], (new, new)),
+        (&["--date", "@5000", "f"], (new, new)),
+        (&["-a", "-d", "@5000", "f"], (new, old.1)),
+        (&["-m", "-d", "@5000", "f"], (old.0, new)),
+        (&["-am", "-d", "@5000", "f"], (new, new)),
+        (&["-a", "-m", "-d", "@5000", "f"], (new, new)),
+        (&["--time=atime", "-d", "@5000", "f"], (new, old.1)),
+        (&["--time=access", "-d", "@5000", "f"], (new, old.1)),
+        (&["--time", "use", "-d", "@5000", "f"], (new, old.1)),
+        (&["--time=mtime", "-d", "@5000", "f"], (old.0, new)),
+        (&["--time=modify", "-d", "@5000", "f"], (old.0, new)),
+        (&["-c", "-d", "@5000", "f"], (new, new)),
+        (&["-h", "-d", "@5000", "f"], (new, new)),
+        (&["-d", "@5000", "--", "f"], (new, new)),
+        // date strings, down to the nanosecond
+        (
+            &["-d", "2024-01-02T03:04:05.123456789Z", "f"],
+            both(1_704_164_645, 123_456_789),
+        ),
+        (&["-d", "@-1.5", "f"], both(-2, 500_000_000)),
+        (
+            &["-d", "Tue, 02 Jan 2024 03:04:05 +0200", "f"],
+            both(1_704_157_445, 0),
+        ),
+        (&["-d", "2024-01-31 +1 month", "f"], both(1_709_337_600, 0)),
+        (
+            &["-d", "2024-01-02 10:00 UTC -1 hour", "f"],
+            both(1_704_186_000, 0),
+        ),
+        (
+            &["-d", "2024-01-02 10:00 -1 hour", "f"],
+            both(1_704_196_800, 0),
+        ),
+        (&["-d", "24-01-02", "f"], both(1_704_153_600, 0)),
+        // POSIX timestamps
+        (&["-t", "202401020304.05", "f"], both(1_704_164_645, 0)),
+        (&["-t", "2401020304", "f"], both(1_704_164_640, 0)),
+        (&["-t", "6901010000", "f"], both(-31_536_000, 0)),
+        (&["-t", "6801010000", "f"], both(3_092_601_600, 0)),
+        (&["-t", "201612312359.60", "f"], both(1_483_228_800, 0)),
+        (&["-m", "-t", "7001010000", "f"], (old.0, at(0, 0))),
+        // reference files
+        (&["-r", "ref", "f"], reference),
+        (&["-rref", "f"], reference),
+        (&["--reference=ref", "-m", "f"], (old.0, reference.1)),
+        (&["--reference", "ref", "-a", "f"], (reference.0, old.1)),
+        (
+            &["-r", "ref", "-d", "+1 hour", "f"],
+            (at(1_200_003_600, 500_000_000), at(1_300_003_600, 250)),
+        ),
+    ];
+
+    for (args, expected) in cases {
+        let dir = Dir::new()?;
+        dir.touch(args).assert().success().stdout("").stderr("");
+        assert_eq!(*expected, dir.times("f")?, "{:?}", args);
+    }
+    Ok(())
+}
+
+#[test]
+fn uses_current_time() -> Result<()> {
+    // Which of the access and modification time change.
+    let cases: &[(&[&str], (bool, bool))] = &[
+        (&["f"], (true, true)),
+        (&["-a", "f"], (true, false)),
+        (&["--time=mtime", "f"], (false, true)),
+        (&["-c", "f"], (true, true)),
+        (&["-h", "f"], (true, true)),
+        (&["-d", "now", "f"], (true, true)),
+        (&["-m", "-d", "now", "f"], (false, true)),
+    ];
+
+    for (args, changed) in cases {
+        let dir = Dir::new()?;
+        // The clock of the file system may be a little behind.
+        let before = at(FileTime::now().unix_seconds() - 1, 0);
+        dir.touch(args).assert().success().stderr("");
+
+        let (atime, mtime) = dir.times("f")?;
+        assert_eq!(changed.0, atime >= before, "{:?}", args);
+        assert_eq!(changed.1, mtime >= before, "{:?}", args);
+        if !changed.0 {
+            assert_eq!(old().0, atime, "{:?}", args);
+        }
+        if !changed.1 {
+            assert_eq!(old().1, mtime, "{:?}", args);
+        }
+    }
     Ok(())
 }
 
 #[test]
-fn test_run_with_not_existing_file() -> Result<()> {
-    Command::cargo_bin("touch")?
-        .arg(tmp_file_path("test_file1"))
+fn uses_relative_dates() -> Result<()> {
+    let dir = Dir::new()?;
+    let now = FileTime::now().unix_seconds();
+    dir.touch(&["--date=3 days ago", "f"]).assert().success();
+
+    let (atime, mtime) = dir.times("f")?;
+    assert_eq!(atime, mtime);
+    assert!((mtime.unix_seconds() - (now - 3 * 86400)).abs() <= 2);
+    Ok(())
+}
+
+#[test]
+fn uses_local_time_zone() -> Result<()> {
+    let cases = [
+        (["-t", "202407010000", "f"], 1_719_806_400),
+        (["-d", "2024-07-01", "f"], 1_719_806_400),
+        (["-d", "jan 5 2024 3pm", "f"], 1_704_484_800),
+    ];
+
+    for (args, secs) in cases {
+        let dir = Dir::new()?;
+        dir.touch(&args)
+            .env("TZ", "EST5EDT,M3.2.0,M11.1.0")
+            .assert()
+            .success();
+        assert_eq!((at(secs, 0), at(secs, 0)), dir.times("f")?, "{:?}", args);
+    }
+
+    // Times that are skipped when daylight saving time starts don't exist.
+    for args in [["-t", "202403100230", "f"], ["-d", "2024-03-10 02:30", "f"]] {
+        Dir::new()?
+            .touch(&args)
+            .env("TZ", "EST5EDT,M3.2.0,M11.1.0")
+            .assert()
+            .failure()
+            .code(1)
+            .stderr(format!("touch: invalid date format '{}'\n", args[1]));
+    }
+
+    // Relative to a reference, a day is 24 hours even though daylight saving time starts or
+    // ends in between.
+    let dir = Dir::new()?;
+    dir.file("dst", (at(1_709_996_400, 0), at(1_730_563_200, 250)))?;
+    dir.touch(&["-r", "dst", "-d", "tomorrow", "f"])
+        .env("TZ", "EST5EDT,M3.2.0,M11.1.0")
         .assert()
         .success();
-    assert_eq!(true, Path::new(&tmp_file_path("test_file1")).exists());
-    fs::remove_file(tmp_file_path("test_file1"))?;
+    assert_eq!(
+        (at(1_710_082_800, 0), at(1_730_649_600, 250)),
+        dir.times("f")?
+    );
+    Ok(())
+}
+
+#[test]
+fn creates_missing_files() -> Result<()> {
+    // Whether touch succeeds, and whether the file `new` exists afterwards.
+    let cases: &[(&[&str], bool, bool)] = &[
+        (&["new"], true, true),
+        (&["-a", "new"], true, true),
+        (&["-m", "new"], true, true),
+        (&["-d", "@5000", "new"], true, true),
+        (&["-r", "ref", "new"], true, true),
+        (&["-c", "new"], true, false),
+        (&["--no-create", "new"], true, false),
+        (&["-c", "-d", "@5000", "new"], true, false),
+        (&["-h", "new"], false, false),
+        (&["-h", "-c", "new"], true, false),
+        (&["-d", "jan 32", "new"], false, false),
+        (&["-r", "missing", "new"], false, false),
+        (&["--time=ctime", "new"], false, false),
+    ];
+
+    for (args, success, exists) in cases {
+        let dir = Dir::new()?;
+        let assert = dir.touch(args).assert();
+        if *success {
+            assert.success().stderr("");
+        } else {
+            assert.failure().code(1);
+        }
+        assert_eq!(*exists, dir.exists("new"), "{:?}", args);
+    }
+
+    // Everything after -- is a file, even if it looks like an option.
+    let dir = Dir::new()?;
+    dir.touch(&["--", "-d", "--time"]).assert().success();
+    assert!(dir.exists("-d") && dir.exists("--time"));
+    Ok(())
+}
+
+#[test]
+fn reports_errors() -> Result<()> {
+    let try_help = "Try 'touch --help' for more information.";
+    let cases: &[(&[&str], String)] = &[
+        (
+            &["-x", "f"],
+            format!("touch: invalid option -- 'x'\n{}\n", try_help),
+        ),
+        (
+            &["--bogus", "f"],
+            format!("touch: unrecognized option '--bogus'\n{}\n", try_help),
+        ),
+        (
+            &["f", "-d"],
+            format!("touch: option requires an argument -- 'd'\n{}\n", try_help),
+        ),
+        (
+            &["f", "--date"],
+            format!(
+                "touch: option '--date' requires an argument\n{}\n",
+                try_help
+            ),
+        ),
+        (
+            &["--no-create=yes", "f"],
+            format!(
+                "touch: option '--no-create' doesn't allow an argument\n{}\n",
+                try_help
+            ),
+        ),
+        (
+            &["--time=ctime", "f"],
+            format!(
+                "touch: invalid argument 'ctime' for '--time'\n\
+                 Valid arguments are:\n  \
+                 - 'atime', 'access', 'use'\n  \
+                 - 'mtime', 'modify'\n{}\n",
+                try_help
+            ),
+        ),
+        (
+            &["-t", "202401010000", "-r", "ref", "f"],
+            format!(
+                "touch: cannot specify times from more than one source\n{}\n",
+                try_help
+            ),
+        ),
+        (
+            &["-d", "now", "-t", "202401010000", "f"],
+            format!(
+                "touch: cannot specify times from more than one source\n{}\n",
+                try_help
+            ),
+        ),
+        (
+            &["-r", "missing", "f"],
+            "touch: failed to get attributes of 'missing': No such file or directory\n".into(),
+        ),
+        (
+            &["missing/f"],
+            "touch: cannot touch 'missing/f': No such file or directory\n".into(),
+        ),
+        (
+            &["f/sub"],
+            "touch: cannot touch 'f/sub': Not a directory\n".into(),
+        ),
+        (
+            // Only missing files are ignored with -c.
+            &["-c", "f/sub"],
+            "touch: setting times of 'f/sub': Not a directory\n".into(),
+        ),
+        (
+            &["-h", "new"],
+            "touch: setting times of 'new': No such file or directory\n".into(),
+        ),
+    ];
+
+    for (args, stderr) in cases {
+        let dir = Dir::new()?;
+        dir.touch(args)
+            .assert()
+            .failure()
+            .code(1)
+            .stdout("")
+            .stderr(stderr.clone());
+        assert_eq!(old(), dir.times("f")?, "{:?}", args);
+    }
+
+    let stamps = [
+        "202402300000",
+        "202401010000.61",
+        "2401010000.5",
+        "0101000",
+        "20240101000000",
+        "2024-1010000",
+    ];
+    for stamp in stamps {
+        Dir::new()?
+            .touch(&["-t", stamp, "f"])
+            .assert()
+            .failure()
+            .code(1)
+            .stderr(format!("touch: invalid date format '{}'\n", stamp));
+    }
+
+    let dates = [
+        "2024-13-01",
+        "jan 32",
+        "24:00",
+        "3 days since",
+        "@1 tomorrow",
+    ];
+    for date in dates {
+        Dir::new()?
+            .touch(&["-d", date, "f"])
+            .assert()
+            .failure()
+            .code(1)
+            .stderr(format!("touch: invalid date format '{}'\n", date));
+    }
+    Ok(())
+}
+
+#[test]
+fn shows_usage_without_files() -> Result<()> {
+    let cases: [&[&str]; 3] = [&[], &["-c"], &["-d", "@5000"]];
+    for args in cases {
+        Dir::new()?
+            .touch(args)
+            .assert()
+            .failure()
+            .code(1)
+            .stderr(contains("USAGE"));
+    }
+    Ok(())
+}
+
+#[test]
+fn shows_help_and_version() -> Result<()> {
+    // Like in GNU, the first of them wins and nothing is touched.
+    let dir = Dir::new()?;
+    dir.touch(&["--help", "missing"])
+        .assert()
+        .success()
+        .stdout(contains("USAGE"))
+        .stderr("");
+    dir.touch(&["--version", "--help", "missing"])
+        .assert()
+        .success()
+        .stdout("touch 0.1.0\n")
+        .stderr("");
+    assert!(!dir.exists("missing"));
+    Ok(())
+}
+
+#[test]
+fn continues_after_failure() -> Result<()> {
+    let dir = Dir::new()?;
+    dir.touch(&["a", "missing/file", "b", "f/sub", "-d", "@5000", "f"])
+        .assert()
+        .failure()
+        .code(1)
+        .stderr(
+            "touch: cannot touch 'missing/file': No such file or directory\n\
+             touch: cannot touch 'f/sub': Not a directory\n",
+        );
+
+    assert!(dir.exists("a") && dir.exists("b"));
+    assert_eq!((at(5000, 0), at(5000, 0)), dir.times("f")?);
     Ok(())
 }
 
-fn tmp_file_path<S: Into<String>>(name: S) -> String {
-    env::temp_dir()
-        .join(Path::new(&name.into()))
-        .to_string_lossy()
-        .to_string()
+#[test]
+fn follows_symbolic_links() -> Result<()> {
+    let dir = Dir::new()?;
+    symlink("f", dir.path("link"))?;
+    let link_mtime = dir.link_times("link")?.1;
+    dir.touch(&["-d", "@5000", "link"]).assert().success();
+    assert_eq!((at(5000, 0), at(5000, 0)), dir.times("f")?);
+    assert_eq!(link_mtime, dir.link_times("link")?.1);
+
+    // The file a dangling link points to is created.
+    symlink("target", dir.path("dangling"))?;
+    dir.touch(&["dangling"]).assert().success();
+    assert!(dir.exists("target"));
+
+    // A link to the reference is followed as well.
+    let dir = Dir::new()?;
+    symlink("ref", dir.path("link"))?;
+    dir.touch(&["-r", "link", "f"]).assert().success();
+    assert_eq!(reference(), dir.times("f")?);
+    Ok(())
 }
 
 #[test]
-fn test_run_with_existing_file() -> Result<()> {
-    File::create(tmp_file_path("test_file2"))?;
-    set_time_from_past(tmp_file_path("test_file2"))?;
+fn changes_symbolic_links_with_no_dereference() -> Result<()> {
+    let old = old();
+    let new = at(5000, 0);
+    let cases: &[(&[&str], (FileTime, FileTime))] = &[
+        (&["-h", "-d", "@5000", "link"], (new, new)),
+        (&["--no-dereference", "-d", "@5000", "link"], (new, new)),
+        (&["-h", "-a", "-d", "@5000", "link"], (new, old.1)),
+        (&["-h", "-m", "-d", "@5000", "link"], (old.0, new)),
+        (&["-hc", "-d", "@5000", "link"], (new, new)),
+    ];
+
+    for (args, expected) in cases {
+        let dir = Dir::new()?;
+        symlink("ref", dir.path("link"))?;
+        filetime::set_symlink_file_times(dir.path("link"), old.0, old.1)?;
 
-    let (orig_mtime, orig_atime) = times(tmp_file_path("test_file2"))?;
+        dir.touch(args).assert().success();
+        assert_eq!(*expected, dir.link_times("link")?, "{:?}", args);
+        assert_eq!(reference(), dir.times("ref")?, "{:?}", args);
+    }
 
-    Command::cargo_bin("touch")?
-        .arg(tmp_file_path("test_file2"))
+    // Dangling links work too, without creating the file they point to.
+    let dir = Dir::new()?;
+    symlink("target", dir.path("dangling"))?;
+    dir.touch(&["-h", "-d", "@5000", "dangling"])
         .assert()
         .success();
+    assert_eq!((new, new), dir.link_times("dangling")?);
+    assert!(!dir.exists("target"));
 
-    let (curr_mtime, curr_atime) = times(tmp_file_path("test_file2"))?;
+    // The reference isn't followed either.
+    let dir = Dir::new()?;
+    symlink("ref", dir.path("link"))?;
+    filetime::set_symlink_file_times(dir.path("link"), at(7, 0), at(8, 0))?;
+    dir.touch(&["-h", "-r", "link", "f"]).assert().success();
+    assert_eq!((at(7, 0), at(8, 0)), dir.times("f")?);
+    Ok(())
+}
 
-    assert!(orig_atime < curr_atime);
-    assert!(orig_mtime < curr_mtime);
-    fs::remove_file(tmp_file_path("test_file2"))?;
+#[test]
+fn changes_standard_output() -> Result<()> {
+    let cases: &[(&[&str], (FileTime, FileTime))] = &[
+        (&["-d", "@5000", "-"], (at(5000, 0), at(5000, 0))),
+        (&["-m", "-d", "@5000", "-"], (old().0, at(5000, 0))),
+        (&["-c", "-d", "@5000", "-"], (at(5000, 0), at(5000, 0))),
+    ];
+
+    for (args, expected) in cases {
+        let dir = Dir::new()?;
+        let output = File::options().append(true).open(dir.path("f"))?;
+        let status = process::Command::new(env!("CARGO_BIN_EXE_touch"))
+            .current_dir(dir.0.path())
+            .args(*args)
+            .stdout(output)
+            .status()?;
+        assert!(status.success(), "{:?}", args);
+        assert_eq!(*expected, dir.times("f")?, "{:?}", args);
+        assert!(!dir.exists("-"));
+    }
     Ok(())
 }
 
-fn set_time_from_past<S: Into<String>>(filepath: S) -> Result<()> {
-    let d = SystemTime::now().duration_since(UNIX_EPOCH)?;
-    let seconds = d.as_secs() - 180;
-    let ft = FileTime::from_unix_time(seconds as i64, 0);
-    filetime::set_file_times(filepath.into(), ft, ft)?;
+#[test]
+fn handles_special_files() -> Result<()> {
+    let dir = Dir::new()?;
+    let mkfifo = process::Command::new("mkfifo")
+        .arg(dir.path("fifo"))
+        .status()?;
+    assert!(mkfifo.success());
+    fs::create_dir(dir.path("dir"))?;
+
+    // Opening the FIFO for writing would block without a reader.
+    dir.touch(&["-d", "@5000", "fifo", "dir"])
+        .timeout(Duration::from_secs(10))
+        .assert()
+        .success();
+    assert_eq!((at(5000, 0), at(5000, 0)), dir.times("fifo")?);
+    assert_eq!((at(5000, 0), at(5000, 0)), dir.times("dir")?);
+
+    // The FIFO isn't opened at all, which would wake up a reader that waits for a writer.
+    let mut reader = process::Command::new("cat").arg(dir.path("fifo")).spawn()?;
+    std::thread::sleep(Duration::from_millis(100));
+    dir.touch(&["fifo"]).assert().success();
+    std::thread::sleep(Duration::from_millis(100));
+    let waiting = reader.try_wait()?.is_none();
+    reader.kill()?;
+    reader.wait()?;
+    assert!(waiting, "the reader of the FIFO was woken up");
     Ok(())
 }
 
-fn times<S: Into<String>>(path: S) -> Result<(FileTime, FileTime)> {
-    let meta = fs::metadata(tmp_file_path(path))?;
-    let mtime = FileTime::from_last_modification_time(&meta);
-    let atime = FileTime::from_last_modification_time(&meta);
-    Ok((mtime, atime))
+#[test]
+fn reports_permission_errors() -> Result<()> {
+    let dir = Dir::new()?;
+    fs::set_permissions(dir.0.path(), fs::Permissions::from_mode(0o755))?;
+    fs::create_dir(dir.path("locked"))?;
+    fs::set_permissions(dir.path("locked"), fs::Permissions::from_mode(0o555))?;
+    fs::set_permissions(dir.path("f"), fs::Permissions::from_mode(0o666))?;
+    // The binary may be somewhere nobody can reach.
+    fs::copy(env!("CARGO_BIN_EXE_touch"), dir.path("touch"))?;
+
+    // What touch reports, where nothing means it succeeds.
+    let cases: &[(&[&str], &str)] = &[
+        (
+            &["locked/new"],
+            "touch: cannot touch 'locked/new': Permission denied\n",
+        ),
+        (&["-c", "locked/new"], ""),
+        // Writing to a file is enough to set its times to the current time.
+        (&["f"], ""),
+    ];
+
+    for (args, stderr) in cases {
+        let mut command = process::Command::new(dir.path("touch"));
+        command.current_dir(dir.0.path()).args(*args);
+        // root may write anywhere, so it runs touch as nobody instead.
+        // SAFETY: setgid and setuid are async-signal-safe, so they may be called after fork.
+        unsafe {
+            command.pre_exec(|| {
+                if libc::geteuid() == 0 && (libc::setgid(65534) != 0 || libc::setuid(65534) != 0) {
+                    return Err(io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+
+        let assert = Command::from_std(command).assert().stderr(*stderr);
+        if stderr.is_empty() {
+            assert.success();
+        } else {
+            assert.failure().code(1);
+        }
+    }
+
+    assert!(!dir.exists("locked/new"));
+    assert!(dir.times("f")?.1 > old().1);
+    Ok(())
 }